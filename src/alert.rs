@@ -0,0 +1,327 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::framework::info::FrameworkInfo;
+
+const NOTIFICATION_TITLE: &str = "Framework System";
+
+/// Alert rules, a rule is disabled when its threshold is not set. Everything
+/// is off until the user asks for it in the config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertsConfig {
+    /// Fire when discharging below this charge percentage
+    pub charge_below: Option<u32>,
+    pub charger_unplugged: bool,
+    pub camera_on: bool,
+    pub microphone_on: bool,
+    pub fan_above_rpm: Option<u16>,
+    pub capacity_loss_above: Option<f32>,
+    /// Minimum time between two alerts of the same kind
+    pub cooldown_secs: u64,
+    pub bell: bool,
+    pub notification: NotificationProtocol,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            charge_below: None,
+            charger_unplugged: false,
+            camera_on: false,
+            microphone_on: false,
+            fan_above_rpm: None,
+            capacity_loss_above: None,
+            cooldown_secs: 300,
+            bell: false,
+            notification: NotificationProtocol::None,
+        }
+    }
+}
+
+/// Escape sequence used to ask the terminal for a desktop notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationProtocol {
+    None,
+    /// iTerm2, WezTerm, Windows Terminal, foot, ghostty
+    Osc9,
+    /// urxvt, foot, WezTerm, VTE based terminals
+    Osc777,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    ChargeLow,
+    ChargerUnplugged,
+    CameraOn,
+    MicrophoneOn,
+    FanHigh,
+    CapacityLoss,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub message: String,
+}
+
+/// Evaluates [`AlertsConfig`] rules against successive snapshots.
+///
+/// Threshold rules fire while the condition holds, switch rules fire when the
+/// state flips. Either way a rule stays quiet for `cooldown_secs` after firing.
+pub struct AlertEngine {
+    config: AlertsConfig,
+    last_fired: HashMap<AlertKind, Instant>,
+    previous: Option<(bool, bool, bool)>,
+}
+
+impl AlertEngine {
+    pub fn new(config: AlertsConfig) -> Self {
+        Self {
+            config,
+            last_fired: HashMap::new(),
+            previous: None,
+        }
+    }
+
+    pub fn set_config(&mut self, config: AlertsConfig) {
+        self.config = config;
+    }
+
+    pub fn config(&self) -> &AlertsConfig {
+        &self.config
+    }
+
+    pub fn evaluate(&mut self, info: &FrameworkInfo, now: Instant) -> Vec<Alert> {
+        let mut candidates = Vec::new();
+        let (was_ac_connected, was_camera_enabled, was_microphone_enabled) =
+            self.previous.unwrap_or((
+                info.is_ac_connected,
+                info.is_camera_enabled,
+                info.is_microphone_enabled,
+            ));
+
+        if let (Some(threshold), Some(charge_percentage)) =
+            (self.config.charge_below, info.charge_percentage)
+        {
+            if !info.is_charging && charge_percentage < threshold {
+                candidates.push(Alert {
+                    kind: AlertKind::ChargeLow,
+                    message: format!("Battery is low: {}%", charge_percentage),
+                });
+            }
+        }
+
+        if self.config.charger_unplugged && was_ac_connected && !info.is_ac_connected {
+            candidates.push(Alert {
+                kind: AlertKind::ChargerUnplugged,
+                message: "Charger unplugged".to_string(),
+            });
+        }
+
+        if self.config.camera_on && !was_camera_enabled && info.is_camera_enabled {
+            candidates.push(Alert {
+                kind: AlertKind::CameraOn,
+                message: "Camera switched on".to_string(),
+            });
+        }
+
+        if self.config.microphone_on && !was_microphone_enabled && info.is_microphone_enabled {
+            candidates.push(Alert {
+                kind: AlertKind::MicrophoneOn,
+                message: "Microphone switched on".to_string(),
+            });
+        }
+
+        if let (Some(threshold), Some(fan_rpm)) = (self.config.fan_above_rpm, &info.fan_rpm) {
+            if let Some(rpm) = fan_rpm.iter().copied().filter(|rpm| *rpm > threshold).max() {
+                candidates.push(Alert {
+                    kind: AlertKind::FanHigh,
+                    message: format!("Fan is spinning at {} RPM", rpm),
+                });
+            }
+        }
+
+        if let (Some(threshold), Some(loss)) = (
+            self.config.capacity_loss_above,
+            info.capacity_loss_percentage,
+        ) {
+            if loss > threshold {
+                candidates.push(Alert {
+                    kind: AlertKind::CapacityLoss,
+                    message: format!("Battery capacity loss reached {:.1}%", loss),
+                });
+            }
+        }
+
+        self.previous = Some((
+            info.is_ac_connected,
+            info.is_camera_enabled,
+            info.is_microphone_enabled,
+        ));
+
+        let cooldown = Duration::from_secs(self.config.cooldown_secs);
+
+        candidates
+            .into_iter()
+            .filter(|alert| match self.last_fired.get(&alert.kind) {
+                Some(fired_at) if now.duration_since(*fired_at) < cooldown => false,
+                _ => {
+                    self.last_fired.insert(alert.kind, now);
+                    true
+                }
+            })
+            .collect()
+    }
+}
+
+/// Ring the bell and ask the terminal to forward the alert to the desktop
+pub fn notify_terminal(config: &AlertsConfig, alert: &Alert) -> std::io::Result<()> {
+    let message = sanitize(&alert.message);
+    let mut stdout = std::io::stdout();
+
+    if config.bell {
+        stdout.write_all(b"\x07")?;
+    }
+
+    match config.notification {
+        NotificationProtocol::None => {}
+        NotificationProtocol::Osc9 => {
+            write!(stdout, "\x1b]9;{}: {}\x1b\\", NOTIFICATION_TITLE, message)?;
+        }
+        NotificationProtocol::Osc777 => {
+            write!(
+                stdout,
+                "\x1b]777;notify;{};{}\x1b\\",
+                NOTIFICATION_TITLE,
+                message.replace(';', ",")
+            )?;
+        }
+    }
+
+    stdout.flush()
+}
+
+// NOTE: control characters would terminate the escape sequence early
+fn sanitize(message: &str) -> String {
+    message.chars().filter(|c| !c.is_control()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        alert::{AlertEngine, AlertKind, AlertsConfig},
+        framework::info::FrameworkInfo,
+    };
+
+    fn kinds(engine: &mut AlertEngine, info: &FrameworkInfo, now: Instant) -> Vec<AlertKind> {
+        engine
+            .evaluate(info, now)
+            .into_iter()
+            .map(|alert| alert.kind)
+            .collect()
+    }
+
+    #[test]
+    fn charge_below_respects_cooldown() {
+        let mut engine = AlertEngine::new(AlertsConfig {
+            charge_below: Some(20),
+            cooldown_secs: 60,
+            ..AlertsConfig::default()
+        });
+        let info = FrameworkInfo {
+            charge_percentage: Some(10),
+            ..FrameworkInfo::default()
+        };
+        let start = Instant::now();
+
+        assert_eq!(kinds(&mut engine, &info, start), vec![AlertKind::ChargeLow]);
+        assert!(kinds(&mut engine, &info, start + Duration::from_secs(30)).is_empty());
+        assert_eq!(
+            kinds(&mut engine, &info, start + Duration::from_secs(61)),
+            vec![AlertKind::ChargeLow]
+        );
+    }
+
+    #[test]
+    fn charge_below_is_quiet_while_charging() {
+        let mut engine = AlertEngine::new(AlertsConfig {
+            charge_below: Some(15),
+            ..AlertsConfig::default()
+        });
+        let info = FrameworkInfo {
+            charge_percentage: Some(5),
+            is_charging: true,
+            ..FrameworkInfo::default()
+        };
+
+        assert!(kinds(&mut engine, &info, Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn nothing_fires_by_default() {
+        let mut engine = AlertEngine::new(AlertsConfig::default());
+        let info = FrameworkInfo {
+            charge_percentage: Some(5),
+            ..FrameworkInfo::default()
+        };
+
+        assert!(kinds(&mut engine, &info, Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn switch_rules_fire_on_transitions_only() {
+        let mut engine = AlertEngine::new(AlertsConfig {
+            charger_unplugged: true,
+            camera_on: true,
+            cooldown_secs: 0,
+            ..AlertsConfig::default()
+        });
+        let now = Instant::now();
+        let plugged = FrameworkInfo {
+            is_ac_connected: true,
+            ..FrameworkInfo::default()
+        };
+        let unplugged_with_camera = FrameworkInfo {
+            is_camera_enabled: true,
+            ..FrameworkInfo::default()
+        };
+
+        // The initial state is not a transition
+        assert!(kinds(&mut engine, &plugged, now).is_empty());
+        assert_eq!(
+            kinds(&mut engine, &unplugged_with_camera, now),
+            vec![AlertKind::ChargerUnplugged, AlertKind::CameraOn]
+        );
+        assert!(kinds(&mut engine, &unplugged_with_camera, now).is_empty());
+    }
+
+    #[test]
+    fn fan_and_capacity_loss_thresholds() {
+        let mut engine = AlertEngine::new(AlertsConfig {
+            charge_below: None,
+            fan_above_rpm: Some(4000),
+            capacity_loss_above: Some(20.0),
+            ..AlertsConfig::default()
+        });
+        let info = FrameworkInfo {
+            fan_rpm: Some(vec![3000, 4500]),
+            capacity_loss_percentage: Some(25.0),
+            ..FrameworkInfo::default()
+        };
+
+        let alerts = engine.evaluate(&info, Instant::now());
+
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].message, "Fan is spinning at 4500 RPM");
+        assert_eq!(alerts[1].kind, AlertKind::CapacityLoss);
+    }
+}
@@ -1,53 +1,125 @@
+use chrono::{Local, NaiveTime};
 use color_eyre::eyre::Report;
-use framework_lib::chromium_ec::{CrosEc, EcError, EcResponseStatus};
+use framework_lib::chromium_ec::{EcError, EcResponseStatus};
 use ratatui::{prelude::Backend, Terminal};
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    config::Config,
+    alert::{self, AlertEngine},
+    config::{layer::Machine, Config},
     event::{Event, EventLoop},
-    framework::{fingerprint::Fingerprint, info::FrameworkInfo, EcErrorWrapper, Framework},
-    tui::Tui,
+    framework::{fingerprint::Fingerprint, info::FrameworkInfo, EcErrorWrapper, Hardware},
+    hooks, last_applied, profile,
+    rules::RulesEngine,
+    smart_charge::SmartCharger,
+    state::State,
+    tui::{theme::custom::Themes, Tui},
 };
 
 pub const APP_TITLE: &str = " Framework System ";
-pub const FOOTER_HELP: &str = "[Tab] Switch panels [Up/Down] Scroll [Enter] Edit/Apply [Left/Right] Adjust value [Esc] Cancel [q] Quit";
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub struct App {
-    framework: Framework,
+    hardware: Box<dyn Hardware>,
     info: FrameworkInfo,
     running: bool,
     tui: Tui,
+    fingerprint: Arc<Fingerprint>,
     config: Config,
+    alerts: AlertEngine,
+    rules: RulesEngine,
+    smart_charger: SmartCharger,
+    state: State,
+    /// Identity of the machine for the machine sections of the config
+    machine: Machine,
+    /// Only monitor, hardware settings are left alone
+    read_only: bool,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum AppEvent {
     Quit,
     SetMaxChargeLimit(u8),
     SetFingerprintBrightness(u8),
     SetKeyboardBrightness(u8),
     SetTickInterval(u64),
+    SetRuleEnabled(usize, bool),
+    ToggleFullCharge,
+    ApplyProfile(String),
+}
+
+impl AppEvent {
+    /// Whether handling the event writes to the EC
+    pub fn changes_hardware(&self) -> bool {
+        matches!(
+            self,
+            AppEvent::SetMaxChargeLimit(_)
+                | AppEvent::SetFingerprintBrightness(_)
+                | AppEvent::SetKeyboardBrightness(_)
+                | AppEvent::ToggleFullCharge
+                | AppEvent::ApplyProfile(_)
+        )
+    }
 }
 
 impl App {
-    pub fn new() -> color_eyre::Result<Self> {
-        let ec = CrosEc::new();
-        let fingerprint = Arc::new(Fingerprint::new(&ec)?);
+    pub fn new(
+        mut hardware: Box<dyn Hardware>,
+        fingerprint: Arc<Fingerprint>,
+        config: Config,
+        read_only: bool,
+    ) -> color_eyre::Result<Self> {
+        // The EC may have lost the settings over a power cycle or a BIOS update
+        // NOTE: a failed write only gets a warning, the TUI starts with what the EC has
+        let reapply_report = if config.reapply_on_start
+            && !config.last_applied.is_empty()
+            && !read_only
+        {
+            Some(
+                match config.last_applied.reapply(hardware.as_mut(), &fingerprint) {
+                    Ok(corrections) => last_applied::report(&corrections),
+                    Err(error) => format!("Couldn't restore the last applied settings: {}", error),
+                },
+            )
+        } else {
+            None
+        };
+
         // Pre-fetch framework info
-        let mut framework = Framework::new(ec, fingerprint.clone());
-        let info = framework.get_info();
+        let info = hardware.get_info()?;
 
-        // Load config (or create default on first startup)
-        let config = Config::load_or_create()?;
-        let tui = Tui::new(fingerprint, &info, config.clone())?;
+        let mut tui = Tui::new(fingerprint.clone(), &info, config.clone())?;
+        let (themes, theme_errors) = Themes::load();
+        tui.set_themes(themes);
+        if !theme_errors.is_empty() {
+            tui.set_error(theme_errors.join("; "));
+        }
+        if let Some(report) = reapply_report {
+            tui.show_toast(report);
+        }
+        let alerts = AlertEngine::new(config.alerts.clone());
+        let rules = RulesEngine::new(config.rules.clone());
+        let state = State::load();
+        let smart_charger =
+            SmartCharger::new(config.smart_charge.clone(), state.charge_plan.clone());
+        let machine = Machine::from_info(&info);
 
         Ok(Self {
-            framework,
+            hardware,
             info,
             running: true,
             tui,
+            fingerprint,
             config,
+            alerts,
+            rules,
+            smart_charger,
+            state,
+            machine,
+            read_only,
         })
     }
 
@@ -58,20 +130,51 @@ impl App {
         let mut event_loop = EventLoop::new();
 
         event_loop.run(Duration::from_millis(self.config.tick_interval_ms));
+        event_loop.watch(self.config.files()?);
         self.tui
             .title
             .set_tick_interval(self.config.tick_interval_ms);
+        self.tui
+            .set_active_profile(self.state.active_profile.clone());
+        self.update_profile_drift();
 
         while self.running {
             self.tui.render(terminal, &self.info)?;
 
             match event_loop.next().await? {
                 Event::Tick => {
-                    self.info = self.framework.get_info();
+                    let info = match self.hardware.get_info() {
+                        Ok(info) => info,
+                        Err(error) => {
+                            // NOTE: e.g. a daemon restart, the last snapshot stays until the next tick
+                            self.tui
+                                .show_toast(format!("Couldn't read the hardware state: {}", error));
+                            continue;
+                        }
+                    };
+                    let previous = std::mem::replace(&mut self.info, info);
+                    self.tui.record_history(&self.info);
+                    self.run_hooks(&previous);
+                    self.check_alerts();
+                    if !self.read_only {
+                        self.apply_rules(&event_loop);
+                        self.apply_charge_plan(&event_loop)?;
+                    }
+                    self.update_profile_drift();
                 }
+                Event::Reload => self.reload_config(&event_loop),
                 Event::Input(event) => {
                     if let Some(app_event) = self.tui.handle_input(event)? {
-                        self.handle_event(app_event, &event_loop)?;
+                        self.handle_event(app_event.clone(), &event_loop)?;
+                        if let AppEvent::SetMaxChargeLimit(value) = app_event {
+                            self.keep_charge_plan_limit(value);
+                        }
+                        if let Err(error) = self.remember(&app_event) {
+                            self.tui.set_error(format!(
+                                "Couldn't save the last applied settings: {}",
+                                error
+                            ));
+                        }
                     }
                 }
             }
@@ -81,14 +184,21 @@ impl App {
     }
 
     fn handle_event(&mut self, event: AppEvent, event_loop: &EventLoop) -> color_eyre::Result<()> {
+        if self.read_only && event.changes_hardware() {
+            self.tui.set_error(
+                "Read-only mode, restart without --read-only to change settings.".to_string(),
+            );
+            return Ok(());
+        }
+
         match event {
             AppEvent::Quit => self.quit(),
             AppEvent::SetMaxChargeLimit(value) => {
-                self.framework.set_max_charge_limit(value)?;
+                self.hardware.set_max_charge_limit(value)?;
                 self.info.max_charge_limit = Some(value);
             }
             AppEvent::SetFingerprintBrightness(percentage) => {
-                match self.framework.set_fp_brightness(percentage) {
+                match self.hardware.set_fp_brightness(percentage) {
                     Err(report) => match report.downcast::<EcErrorWrapper>() {
                         Ok(EcErrorWrapper(EcError::Response(EcResponseStatus::InvalidVersion))) => {
                             self.tui.set_error(
@@ -109,19 +219,291 @@ impl App {
                 }
             }
             AppEvent::SetKeyboardBrightness(percentage) => {
-                self.framework.set_kb_brightness(percentage);
+                self.hardware.set_kb_brightness(percentage)?;
                 self.info.kb_brightness_percentage = Some(percentage);
             }
             AppEvent::SetTickInterval(interval_ms) => {
-                self.config.set_tick_interval(interval_ms)?;
+                if let Err(error) = self.config.set_tick_interval(interval_ms) {
+                    self.tui
+                        .set_error(format!("Couldn't save the tick interval: {}", error));
+                }
                 event_loop.set_tick_interval(Duration::from_millis(interval_ms));
                 self.tui.title.set_tick_interval(interval_ms);
             }
+            AppEvent::SetRuleEnabled(index, enabled) => {
+                if let Err(error) = self.config.set_rule_enabled(index, enabled) {
+                    self.tui
+                        .set_error(format!("Couldn't save the rule: {}", error));
+                }
+                self.rules.set_enabled(index, enabled);
+                self.tui.set_rule_statuses(self.rules.statuses());
+            }
+            AppEvent::ToggleFullCharge => {
+                let restore = if self.smart_charger.plan().is_some() {
+                    self.smart_charger.cancel()
+                } else {
+                    self.smart_charger
+                        .schedule(None, &self.info, Local::now().naive_local())
+                };
+
+                self.save_charge_plan(restore, event_loop)?;
+            }
+            AppEvent::ApplyProfile(name) => {
+                match profile::activate(
+                    &name,
+                    &self.config.profiles,
+                    self.hardware.as_mut(),
+                    &self.fingerprint,
+                    &mut self.state,
+                ) {
+                    Ok(()) => {
+                        match self.hardware.get_info() {
+                            Ok(info) => self.info = info,
+                            Err(error) => self
+                                .tui
+                                .show_toast(format!("Couldn't read the hardware state: {}", error)),
+                        }
+                        if let Some(limit) = self
+                            .config
+                            .profiles
+                            .get(&name)
+                            .and_then(|profile| profile.max_charge_limit)
+                        {
+                            self.keep_charge_plan_limit(limit);
+                        }
+                        self.tui.set_active_profile(Some(name.clone()));
+                        self.tui.show_toast(format!("Profile {} applied", name));
+                        self.update_profile_drift();
+                    }
+                    Err(error) => {
+                        self.tui
+                            .set_error(format!("Couldn't apply profile {}: {}", name, error));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply the config files again, a broken edit keeps the current config
+    fn reload_config(&mut self, event_loop: &EventLoop) {
+        let config = match self.config.reload(&self.machine) {
+            Ok(config) => config,
+            Err(error) => {
+                self.tui
+                    .show_toast(format!("Config not reloaded: {}", error));
+                return;
+            }
+        };
+        let same =
+            |a: &Config, b: &Config| toml::Table::try_from(a).ok() == toml::Table::try_from(b).ok();
+        // NOTE: the app's own saves land here too, only edits made elsewhere are announced
+        let edited = !same(&config, &self.config) && !same(&config, self.tui.config());
+
+        if let Err(error) = self.tui.reload_config(&self.machine) {
+            self.tui
+                .show_toast(format!("Config not reloaded: {}", error));
+            return;
+        }
+        if same(&config, &self.config) {
+            return;
+        }
+
+        if config.tick_interval_ms != self.config.tick_interval_ms {
+            event_loop.set_tick_interval(Duration::from_millis(config.tick_interval_ms));
+            self.tui.title.set_tick_interval(config.tick_interval_ms);
+        }
+        self.alerts.set_config(config.alerts.clone());
+        self.rules.set_rules(config.rules.clone());
+        self.tui.set_rule_statuses(self.rules.statuses());
+        self.smart_charger = SmartCharger::new(
+            config.smart_charge.clone(),
+            self.smart_charger.plan().cloned(),
+        );
+        self.config = config;
+        self.update_profile_drift();
+        if edited {
+            self.tui.show_toast("Config reloaded".to_string());
+        }
+    }
+
+    fn check_alerts(&mut self) {
+        for alert in self.alerts.evaluate(&self.info, Instant::now()) {
+            // NOTE: a terminal that can't take the escape sequences still gets the toast
+            let _ = alert::notify_terminal(self.alerts.config(), &alert);
+            self.tui.show_toast(alert.message);
+        }
+    }
+
+    fn run_hooks(&mut self, previous: &FrameworkInfo) {
+        for event in hooks::detect(&self.config.hooks, previous, &self.info) {
+            if let Err(error) = hooks::run(&self.config.hooks, &event, &self.info) {
+                self.tui
+                    .show_toast(format!("Hook {} failed: {}", event.name(), error));
+            }
+        }
+    }
+
+    /// A rule that can't be applied is reported once and disabled for the session,
+    /// the other rules and the TUI keep going
+    fn apply_rules(&mut self, event_loop: &EventLoop) {
+        let now = Local::now().naive_local();
+
+        // NOTE: a charge plan raises the limit until the battery is full, rules wait for it
+        self.rules
+            .hold_charge_limit(self.smart_charger.plan().is_some());
+        for (index, event) in self.rules.evaluate(&self.info, &self.fingerprint, now) {
+            if let Err(error) = self.handle_event(event, event_loop) {
+                // NOTE: rules are level triggered, a failing one would fail again on every tick
+                self.rules.set_enabled(index, false);
+                let name = self.rules.statuses()[index].name.clone();
+
+                self.tui.set_error(format!(
+                    "Rule {} couldn't be applied and was disabled: {}",
+                    name, error
+                ));
+            }
+        }
+        self.tui.set_rule_statuses(self.rules.statuses());
+    }
+
+    /// Show `message` in the error popup, e.g. a problem found before the TUI started
+    pub fn set_error(&mut self, message: String) {
+        self.tui.set_error(message);
+    }
+
+    /// Plan a full charge by the next `full_by`, e.g. from the command line
+    pub fn schedule_full_charge(&mut self, full_by: NaiveTime) -> color_eyre::Result<()> {
+        if self.read_only {
+            return Err(color_eyre::eyre::eyre!(
+                "--full-by changes the charge limit and can't be used with --read-only."
+            ));
+        }
+
+        let now = Local::now().naive_local();
+        let restore = self.smart_charger.schedule(Some(full_by), &self.info, now);
+
+        if let Some(AppEvent::SetMaxChargeLimit(value)) = restore {
+            self.hardware.set_max_charge_limit(value)?;
+        }
+        self.state
+            .set_charge_plan(self.smart_charger.plan().cloned())
+    }
+
+    fn apply_charge_plan(&mut self, event_loop: &EventLoop) -> color_eyre::Result<()> {
+        let now = Local::now().naive_local();
+        let plan = self.smart_charger.plan().cloned();
+        let event = self.smart_charger.tick(&self.info, now);
+
+        if event.is_some() || plan.as_ref() != self.smart_charger.plan() {
+            self.save_charge_plan(event, event_loop)?;
+        } else {
+            self.tui
+                .set_charge_plan_status(self.smart_charger.status(&self.info));
+        }
+
+        Ok(())
+    }
+
+    fn save_charge_plan(
+        &mut self,
+        event: Option<AppEvent>,
+        event_loop: &EventLoop,
+    ) -> color_eyre::Result<()> {
+        if let Some(event) = event {
+            self.handle_event(event, event_loop)?;
         }
+        self.state
+            .set_charge_plan(self.smart_charger.plan().cloned())?;
+        self.tui
+            .set_charge_plan_status(self.smart_charger.status(&self.info));
 
         Ok(())
     }
 
+    /// A max charge limit chosen while a charge plan is pending is the one it puts back
+    fn keep_charge_plan_limit(&mut self, limit: u8) {
+        if self.read_only || !self.smart_charger.set_normal_limit(limit) {
+            return;
+        }
+
+        if let Err(error) = self
+            .state
+            .set_charge_plan(self.smart_charger.plan().cloned())
+        {
+            self.tui
+                .set_error(format!("Couldn't save the charge plan: {}", error));
+        }
+        self.tui
+            .set_charge_plan_status(self.smart_charger.status(&self.info));
+    }
+
+    /// Record the values the user chose so they can be re-applied on startup
+    fn remember(&mut self, event: &AppEvent) -> color_eyre::Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        match event {
+            AppEvent::SetMaxChargeLimit(value) => {
+                let value = *value;
+
+                // NOTE: setting only the max charge limit resets the min one
+                self.config.remember(|last_applied| {
+                    last_applied.max_charge_limit = Some(value);
+                    last_applied.min_charge_limit = None;
+                })
+            }
+            AppEvent::SetKeyboardBrightness(percentage) => {
+                let percentage = *percentage;
+
+                self.config
+                    .remember(|last_applied| last_applied.keyboard_brightness = Some(percentage))
+            }
+            AppEvent::SetFingerprintBrightness(percentage)
+                if self.info.fp_brightness_percentage == Some(*percentage) =>
+            {
+                let percentage = *percentage;
+
+                self.config
+                    .remember(|last_applied| last_applied.fingerprint_brightness = Some(percentage))
+            }
+            AppEvent::ApplyProfile(name) if self.state.active_profile.as_ref() == Some(name) => {
+                let Some(profile) = self.config.profiles.get(name).cloned() else {
+                    return Ok(());
+                };
+
+                self.config.remember(|last_applied| {
+                    if profile.max_charge_limit.is_some() || profile.min_charge_limit.is_some() {
+                        last_applied.max_charge_limit = profile.max_charge_limit;
+                        last_applied.min_charge_limit = profile.min_charge_limit;
+                    }
+                    if profile.keyboard_brightness.is_some() {
+                        last_applied.keyboard_brightness = profile.keyboard_brightness;
+                    }
+                    if profile.fingerprint_brightness.is_some() {
+                        last_applied.fingerprint_brightness = profile.fingerprint_brightness;
+                    }
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn update_profile_drift(&mut self) {
+        let profile = self.state.active_profile.as_ref().and_then(|name| {
+            let profile = self.config.profiles.get(name)?;
+
+            Some((
+                name.clone(),
+                !profile.drift(&self.info, &self.fingerprint).is_empty(),
+            ))
+        });
+
+        self.tui.title.set_profile(profile);
+    }
+
     fn quit(&mut self) {
         self.running = false;
     }
@@ -1,80 +1,789 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
-use crate::tui::theme::ThemeVariant;
+pub mod layer;
 
+use self::layer::{system_config_path, Layer, Machine, MACHINE_SECTIONS};
+use crate::{
+    alert::AlertsConfig,
+    hooks::HooksConfig,
+    last_applied::LastApplied,
+    paths,
+    profile::{self, default_profiles, Profile},
+    rules::{self, Rule},
+    smart_charge::SmartChargeConfig,
+    tui::{
+        keymap::Keymap,
+        layout::DashboardConfig,
+        theme::{auto::AutoThemeConfig, palette::ColorSupport, ThemeId, ThemeVariant},
+    },
+};
+
+/// Schema version written to new files
+pub const CONFIG_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` upgrades a version `n` file to version `n + 1`,
+/// files without a `version` field are version 0
+const MIGRATIONS: [fn(&mut toml::Table); CONFIG_VERSION as usize] = [migrate_v0];
+
+fn migrate_v0(_table: &mut toml::Table) {
+    // NOTE: unversioned files already have the version 1 layout, they only get the version stamped
+}
+
+/// Layer read from a file and the version it was upgraded from
+type ParsedLayer = (Layer, Option<u32>);
+
+/// Missing fields are taken from [`Config::default`]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
-    pub theme: ThemeVariant,
-    #[serde(default = "default_tick_interval")]
+    pub version: u32,
+    pub theme: ThemeId,
+    /// Light/dark pair picked from the terminal background
+    pub auto_theme: AutoThemeConfig,
+    /// Colours the terminal supports, detected from the environment by default
+    pub color_support: ColorSupport,
+    /// Keys bound to every action
+    pub keys: Keymap,
+    /// Capture the mouse, the terminal's own text selection needs Shift while it's on
+    pub mouse: bool,
+    /// Panels shown on the dashboard, their order and sizes
+    pub dashboard: DashboardConfig,
     pub tick_interval_ms: u64,
+    /// Re-apply `last_applied` when the app or the daemon starts
+    pub reapply_on_start: bool,
+    pub last_applied: LastApplied,
+    pub alerts: AlertsConfig,
+    pub hooks: HooksConfig,
+    #[serde(deserialize_with = "rules::deserialize_rules")]
+    pub rules: Vec<Rule>,
+    pub smart_charge: SmartChargeConfig,
+    #[serde(deserialize_with = "profile::deserialize_profiles")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// File the config was loaded from, the default location when unset
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// Keep every change in memory only
+    #[serde(skip)]
+    no_save: bool,
+    /// File and section every value was read from, by dotted key
+    #[serde(skip)]
+    sources: BTreeMap<String, String>,
+    /// Machine the machine sections were matched against, changes to values
+    /// they set are saved in them
+    #[serde(skip)]
+    machine: Machine,
+    /// Values set for the session by their source, e.g. the command line, in the
+    /// order they were applied. They are kept over every file when the config is
+    /// reloaded until they're changed during the session
+    #[serde(skip)]
+    overrides: Vec<(String, toml::Table)>,
+}
+
+/// Config file that couldn't be parsed, it's skipped for the session
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub path: PathBuf,
+    /// Position of the error, 1-based
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    /// Copy of the broken file, made before anything could overwrite it
+    pub backup: Option<PathBuf>,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid config {}:{}:{}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message
+        )?;
+
+        match &self.backup {
+            Some(backup) => write!(f, ". Skipped, backup in {}", backup.display()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ConfigError {
+    fn new(path: &Path, content: &str, error: &toml::de::Error) -> Self {
+        let offset = error.span().map(|span| span.start).unwrap_or(0);
+
+        Self::at(path, content, offset, error.message().trim().to_string())
+    }
+
+    fn at(path: &Path, content: &str, offset: usize, message: String) -> Self {
+        let (line, column) = position(content, offset);
+
+        Self {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message,
+            backup: None,
+        }
+    }
+}
+
+/// 1-based line and column of the byte `offset`
+fn position(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map(|line| line.chars().count())
+        .unwrap_or(0)
+        + 1;
+
+    (line, column)
 }
 
-fn default_tick_interval() -> u64 {
-    1000
+/// Copy `path` next to itself as `<name><suffix>`
+fn backup(path: &Path, suffix: &str) -> color_eyre::Result<PathBuf> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(suffix);
+    let backup = PathBuf::from(backup);
+
+    fs::copy(path, &backup)?;
+    paths::hand_over(&backup)?;
+
+    Ok(backup)
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            theme: ThemeVariant::Default,
+            version: CONFIG_VERSION,
+            theme: ThemeId::Builtin(ThemeVariant::Default),
+            auto_theme: AutoThemeConfig::default(),
+            color_support: ColorSupport::default(),
+            keys: Keymap::default(),
+            mouse: true,
+            dashboard: DashboardConfig::default(),
             tick_interval_ms: 1000,
+            reapply_on_start: false,
+            last_applied: LastApplied::default(),
+            alerts: AlertsConfig::default(),
+            hooks: HooksConfig::default(),
+            rules: Vec::new(),
+            smart_charge: SmartChargeConfig::default(),
+            profiles: default_profiles(),
+            path: None,
+            no_save: false,
+            sources: BTreeMap::new(),
+            machine: Machine::default(),
+            overrides: Vec::new(),
         }
     }
 }
 
 impl Config {
-    /// Get the config file path
+    /// Get the config file path, moving the file from the data directory
+    /// where older versions kept it
     fn config_path() -> color_eyre::Result<PathBuf> {
-        let data_dir = dirs::data_local_dir()
-            .ok_or_else(|| color_eyre::eyre::eyre!("Could not determine local data directory"))?;
+        let config_path = paths::config_dir()?.join("config.toml");
+
+        if !config_path.exists() {
+            if let Some(legacy_path) = paths::legacy_config_paths()
+                .into_iter()
+                .find(|path| path.exists())
+            {
+                if fs::rename(&legacy_path, &config_path).is_err() {
+                    // NOTE: the directories may be on different file systems
+                    fs::copy(&legacy_path, &config_path)?;
+                    fs::remove_file(&legacy_path)?;
+                }
+                paths::hand_over(&config_path)?;
+            }
+        }
 
-        let config_dir = data_dir.join("framework-tool-tui");
-        fs::create_dir_all(&config_dir)?;
+        Ok(config_path)
+    }
 
-        Ok(config_dir.join("config.toml"))
+    /// Load configuration from the system and the user file, or create the
+    /// user file if it doesn't exist.
+    ///
+    /// A file that can't be parsed is left as is (the user file is backed up
+    /// first) and skipped, the errors are returned so they can be shown to the user.
+    pub fn load_or_create(machine: &Machine) -> color_eyre::Result<(Self, Vec<ConfigError>)> {
+        Self::load_or_create_from(None, machine)
     }
 
-    /// Load configuration from file, or create default if it doesn't exist
-    pub fn load_or_create() -> color_eyre::Result<Self> {
-        match Self::load() {
-            Ok(config) => Ok(config),
-            Err(_) => {
-                // First startup or invalid config - create default
-                let config = Config::default();
-                config.save()?;
-                Ok(config)
+    /// Same as [`Config::load_or_create`], but with the user file at `path` when given
+    pub fn load_or_create_from(
+        path: Option<&Path>,
+        machine: &Machine,
+    ) -> color_eyre::Result<(Self, Vec<ConfigError>)> {
+        let user_path = match path {
+            Some(path) => path.to_path_buf(),
+            None => Self::config_path()?,
+        };
+        let mut layers = Vec::new();
+        let mut errors = Vec::new();
+
+        if let Some(system_path) = system_config_path() {
+            match Self::read_layer(&system_path)? {
+                Some(Ok((layer, _))) => layers.push(layer),
+                Some(Err(error)) => errors.push(error),
+                None => {}
+            }
+        }
+
+        match Self::read_layer(&user_path)? {
+            Some(Ok((layer, migrated_from))) => {
+                if let Some(version) = migrated_from {
+                    backup(&user_path, &format!(".v{}.bak", version))?;
+                    write_table(&user_path, &layer.table)?;
+                }
+                layers.push(layer);
+            }
+            Some(Err(mut error)) => {
+                error.backup = Some(backup(&user_path, ".bak")?);
+                errors.push(error);
+            }
+            None => {
+                // First startup - values left out of the file keep their defaults
+                write_table(&user_path, &version_table())?;
             }
         }
+
+        let config = Self::resolve(&layers, machine)?;
+
+        Ok((
+            Config {
+                path: path.map(Path::to_path_buf),
+                ..config
+            },
+            errors,
+        ))
     }
 
-    /// Save configuration to file
-    pub fn save(&self) -> color_eyre::Result<()> {
-        let config_path = Self::config_path()?;
-        let content = toml::to_string_pretty(self)?;
-        fs::write(&config_path, content)?;
+    /// Validate the system file and the user file at `path` (the default one
+    /// when unset) without writing anything. Returns the config the app would
+    /// run with and the version the user file would be upgraded from.
+    pub fn check(
+        path: Option<&Path>,
+        machine: &Machine,
+    ) -> color_eyre::Result<Result<(Self, Option<u32>), ConfigError>> {
+        let user_path = match path {
+            Some(path) => path.to_path_buf(),
+            None => Self::config_path()?,
+        };
+        let mut layers = Vec::new();
+        let mut migrated_from = None;
+
+        for (index, layer_path) in system_config_path()
+            .into_iter()
+            .chain([user_path])
+            .enumerate()
+        {
+            match Self::read_layer(&layer_path)? {
+                Some(Ok((layer, version))) => {
+                    if index > 0 {
+                        migrated_from = version;
+                    }
+                    layers.push(layer);
+                }
+                Some(Err(error)) => return Ok(Err(error)),
+                None => {}
+            }
+        }
+
+        Ok(Ok((Self::resolve(&layers, machine)?, migrated_from)))
+    }
+
+    /// Where the value of the dotted `key` comes from, e.g. `alerts.charge_below`
+    pub fn source(&self, key: &str) -> &str {
+        self.sources
+            .get(key)
+            .map(String::as_str)
+            .unwrap_or("default")
+    }
+
+    /// Apply values that win over every file for the rest of the session,
+    /// `source` tells where they come from, e.g. "command line"
+    pub fn set_overrides(
+        &mut self,
+        source: &str,
+        overrides: toml::Table,
+    ) -> color_eyre::Result<()> {
+        let mut table = toml::Table::try_from(&*self)?;
+        let mut sources = std::mem::take(&mut self.sources);
+        layer::merge_table(&mut table, overrides.clone(), "", source, &mut sources);
+
+        let mut all = std::mem::take(&mut self.overrides);
+        match all.iter_mut().find(|(name, _)| name == source) {
+            Some((_, existing)) => *existing = overrides,
+            None => all.push((source.to_string(), overrides)),
+        }
+
+        *self = Config {
+            path: self.path.take(),
+            no_save: self.no_save,
+            sources,
+            machine: std::mem::take(&mut self.machine),
+            overrides: all,
+            ..table.try_into()?
+        };
+
         Ok(())
     }
 
+    /// Read the files again, keeping the overrides
+    pub fn reload(&self, machine: &Machine) -> color_eyre::Result<Self> {
+        let (config, _) = Self::check(self.path.as_deref(), machine)?
+            .map_err(|error| color_eyre::eyre::eyre!(error.to_string()))?;
+        let mut config = Config {
+            path: self.path.clone(),
+            no_save: self.no_save,
+            ..config
+        };
+        for (source, overrides) in &self.overrides {
+            config.set_overrides(source, overrides.clone())?;
+        }
+
+        Ok(config)
+    }
+
+    /// Files the config is read from, whether they exist or not
+    pub fn files(&self) -> color_eyre::Result<Vec<PathBuf>> {
+        Ok(system_config_path()
+            .into_iter()
+            .chain([self.path()?])
+            .collect())
+    }
+
+    fn resolve(layers: &[Layer], machine: &Machine) -> color_eyre::Result<Self> {
+        let (merged, sources) = layer::merge(layers, machine);
+        let config = merged.try_into::<Config>()?;
+
+        Ok(Config {
+            sources,
+            machine: machine.clone(),
+            ..config
+        })
+    }
+
+    /// Parse the file at `path`, `None` when it doesn't exist
+    fn read_layer(path: &Path) -> color_eyre::Result<Option<Result<ParsedLayer, ConfigError>>> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(Some(Self::parse(path, &content).map(|(table, version)| {
+            let layer = Layer {
+                name: path.display().to_string(),
+                table,
+            };
+
+            (layer, version)
+        })))
+    }
+
+    /// Parse and validate `content`, upgrading it to [`CONFIG_VERSION`] when
+    /// it was written for an older schema. The old version is returned
+    /// alongside in that case.
+    /// `version` of the file, 0 when it predates versioning. A file written by a
+    /// newer release is rejected instead of being read with the wrong schema
+    fn version(path: &Path, content: &str) -> Result<u32, ConfigError> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: Option<toml::Spanned<toml::Value>>,
+        }
+
+        let versioned = toml::from_str::<Versioned>(content)
+            .map_err(|error| ConfigError::new(path, content, &error))?;
+        let Some(version) = versioned.version else {
+            return Ok(0);
+        };
+        let error = |message| ConfigError::at(path, content, version.span().start, message);
+        let number = version
+            .get_ref()
+            .as_integer()
+            .and_then(|number| u32::try_from(number).ok())
+            .ok_or_else(|| {
+                error(format!(
+                    "version {} isn't a whole number",
+                    version.get_ref()
+                ))
+            })?;
+
+        if number > CONFIG_VERSION {
+            return Err(error(format!(
+                "version {} is newer than {}, the one this release understands",
+                number, CONFIG_VERSION
+            )));
+        }
+
+        Ok(number)
+    }
+
+    fn parse(path: &Path, content: &str) -> Result<(toml::Table, Option<u32>), ConfigError> {
+        let mut table = toml::from_str::<toml::Table>(content)
+            .map_err(|error| ConfigError::new(path, content, &error))?;
+        let version = Self::version(path, content)?;
+
+        let migrated_from = if version == CONFIG_VERSION {
+            toml::from_str::<Config>(content)
+                .map_err(|error| ConfigError::new(path, content, &error))?;
+
+            None
+        } else {
+            for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+                migrate(&mut table);
+                table.insert("version".to_string(), toml::Value::Integer(from as i64 + 1));
+            }
+
+            table.clone().try_into::<Config>().map_err(|error| {
+                // NOTE: the migrated table has no positions, take them from the
+                // original file when the error is already there
+                let error = toml::from_str::<Config>(content).err().unwrap_or(error);
+
+                ConfigError::new(path, content, &error)
+            })?;
+
+            Some(version)
+        };
+
+        for section in MACHINE_SECTIONS {
+            let Some(toml::Value::Table(entries)) = table.get(section) else {
+                continue;
+            };
+
+            for (key, values) in entries {
+                values.clone().try_into::<Config>().map_err(|error| {
+                    let mut error = ConfigError::new(path, content, &error);
+                    error.message = format!("[{}.\"{}\"] {}", section, key, error.message);
+
+                    error
+                })?;
+            }
+        }
+
+        Ok((table, migrated_from))
+    }
+
+    /// Stop writing changes to the file for the rest of the session
+    pub fn set_no_save(&mut self, no_save: bool) {
+        self.no_save = no_save;
+    }
+
     /// Update the theme and save
-    pub fn set_theme(&mut self, theme: ThemeVariant) -> color_eyre::Result<()> {
-        self.theme = theme;
-        self.save()
+    pub fn set_theme(&mut self, theme: impl Into<ThemeId>) -> color_eyre::Result<()> {
+        let theme = theme.into();
+        self.update(|config| config.theme = theme)
     }
 
     /// Update the tick interval and save
     pub fn set_tick_interval(&mut self, tick_interval_ms: u64) -> color_eyre::Result<()> {
-        self.tick_interval_ms = tick_interval_ms;
-        self.save()
+        self.update(|config| config.tick_interval_ms = tick_interval_ms)
+    }
+
+    /// Enable or disable a rule and save
+    pub fn set_rule_enabled(&mut self, index: usize, enabled: bool) -> color_eyre::Result<()> {
+        self.update(|config| {
+            if let Some(rule) = config.rules.get_mut(index) {
+                rule.enabled = enabled;
+            }
+        })
     }
 
-    fn load() -> color_eyre::Result<Self> {
-        let config_path = Self::config_path()?;
-        let content = fs::read_to_string(&config_path)?;
-        let config = toml::from_str::<Config>(&content)?;
+    /// Record values chosen by the user and save
+    pub fn remember(&mut self, remember: impl Fn(&mut LastApplied)) -> color_eyre::Result<()> {
+        self.update(|config| remember(&mut config.last_applied))
+    }
 
-        Ok(config)
+    /// Apply `update` to this copy and write the values it changed to the user
+    /// file. Everything else in the file is kept, so changes saved through
+    /// other copies of the config and values from other layers aren't overwritten.
+    /// A user file that can't be parsed isn't written, the change is only kept in memory.
+    fn update(&mut self, update: impl Fn(&mut Config)) -> color_eyre::Result<()> {
+        let before = toml::Table::try_from(&*self)?;
+        update(self);
+        let after = toml::Table::try_from(&*self)?;
+
+        // NOTE: the change replaces the command line value, a reload takes it from the file
+        for (_, overrides) in &mut self.overrides {
+            layer::remove_changes(overrides, &before, &after);
+        }
+
+        if self.no_save {
+            return Ok(());
+        }
+
+        let path = self.path()?;
+        // NOTE: a broken file is left for the user to fix, the change is kept for the session
+        let mut table = match Self::read_layer(&path)? {
+            Some(Ok((layer, _))) => layer.table,
+            Some(Err(error)) => {
+                return Err(color_eyre::eyre::eyre!(
+                    "{}. The change is kept until the file is fixed",
+                    error
+                ))
+            }
+            None => version_table(),
+        };
+
+        layer::write_machine_changes(&mut table, &self.machine, &before, &after);
+        write_table(&path, &table)
+    }
+
+    fn path(&self) -> color_eyre::Result<PathBuf> {
+        match &self.path {
+            Some(path) => Ok(path.clone()),
+            None => Self::config_path(),
+        }
+    }
+}
+
+fn version_table() -> toml::Table {
+    toml::Table::from_iter([(
+        "version".to_string(),
+        toml::Value::Integer(CONFIG_VERSION as i64),
+    )])
+}
+
+fn write_table(path: &Path, table: &toml::Table) -> color_eyre::Result<()> {
+    fs::write(path, toml::to_string_pretty(table)?)?;
+    paths::hand_over(path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::{layer::Machine, Config, CONFIG_VERSION},
+        tui::theme::ThemeVariant,
+    };
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "framework-tool-tui-{}-{}.toml",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn invalid_file_is_reported_and_backed_up() {
+        let path = temp_path("invalid");
+        let content = "tick_interval_ms = 500\ntheme = 5\n";
+        std::fs::write(&path, content).unwrap();
+
+        let (config, errors) =
+            Config::load_or_create_from(Some(&path), &Machine::default()).unwrap();
+        let error = errors[0].clone();
+        let backup = error.backup.clone().unwrap();
+
+        assert_eq!((error.line, error.column), (2, 9));
+        assert_eq!(config.tick_interval_ms, 1000);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), content);
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&backup).unwrap();
+    }
+
+    #[test]
+    fn unversioned_file_is_migrated_with_a_backup() {
+        let path = temp_path("unversioned");
+        let content = "theme = \"dracula\"\ntick_interval_ms = 500\n";
+        std::fs::write(&path, content).unwrap();
+
+        let (config, errors) =
+            Config::load_or_create_from(Some(&path), &Machine::default()).unwrap();
+        let mut backup = path.clone().into_os_string();
+        backup.push(".v0.bak");
+
+        assert!(errors.is_empty());
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.theme, ThemeVariant::Dracula);
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), content);
+
+        let (checked, migrated_from) = Config::check(Some(&path), &Machine::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(checked.tick_interval_ms, 500);
+        assert_eq!(
+            checked.source("tick_interval_ms"),
+            path.display().to_string()
+        );
+        assert_eq!(checked.source("alerts.bell"), "default");
+        assert_eq!(migrated_from, None);
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&backup).unwrap();
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let path = temp_path("unknown-version");
+        let error = |content: &str| {
+            let error = Config::parse(&path, content).unwrap_err();
+
+            (error.line, error.column, error.message)
+        };
+
+        assert_eq!(
+            error("theme = \"dracula\"\nversion = \"1\"\n"),
+            (2, 11, "version \"1\" isn't a whole number".to_string())
+        );
+        assert_eq!(
+            error("version = -1\n"),
+            (1, 11, "version -1 isn't a whole number".to_string())
+        );
+        assert_eq!(
+            error("version = 2\n"),
+            (
+                1,
+                11,
+                "version 2 is newer than 1, the one this release understands".to_string()
+            )
+        );
+        assert_eq!(Config::parse(&path, "version = 1\n").unwrap().1, None);
+    }
+
+    #[test]
+    fn missing_fields_are_filled_from_defaults() {
+        let config: Config = toml::from_str("tick_interval_ms = 500").unwrap();
+
+        assert_eq!(config.theme, ThemeVariant::Default);
+        assert_eq!(config.tick_interval_ms, 500);
+        assert_eq!(config.profiles.len(), 4);
+    }
+
+    #[test]
+    fn no_save_leaves_the_file_untouched() {
+        let path = temp_path("no-save");
+        let _ = std::fs::remove_file(&path);
+
+        let (mut config, _) =
+            Config::load_or_create_from(Some(&path), &Machine::default()).unwrap();
+        let original = std::fs::read_to_string(&path).unwrap();
+
+        config.set_no_save(true);
+        config.set_theme(ThemeVariant::Dracula).unwrap();
+
+        assert_eq!(config.theme, ThemeVariant::Dracula);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reload_keeps_overrides_and_rejects_broken_edits() {
+        let path = temp_path("reload");
+        std::fs::write(&path, "version = 1\ntick_interval_ms = 500\n").unwrap();
+
+        let (mut config, _) =
+            Config::load_or_create_from(Some(&path), &Machine::default()).unwrap();
+        config
+            .set_overrides("auto theme", toml::from_str("theme = \"dracula\"").unwrap())
+            .unwrap();
+        config
+            .set_overrides(
+                "command line",
+                toml::from_str("tick_interval_ms = 300").unwrap(),
+            )
+            .unwrap();
+        assert_eq!(config.source("theme"), "auto theme");
+
+        std::fs::write(
+            &path,
+            "version = 1\ntick_interval_ms = 800\ntheme = \"gameboy\"\n",
+        )
+        .unwrap();
+        let reloaded = config.reload(&Machine::default()).unwrap();
+        assert_eq!(reloaded.tick_interval_ms, 300);
+        assert_eq!(reloaded.source("tick_interval_ms"), "command line");
+        assert_eq!(reloaded.theme, ThemeVariant::Dracula);
+        assert_eq!(reloaded.source("theme"), "auto theme");
+
+        std::fs::write(&path, "tick_interval_ms = \"fast\"\n").unwrap();
+        assert!(reloaded.reload(&Machine::default()).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn broken_file_is_not_overwritten() {
+        let path = temp_path("broken");
+        std::fs::write(&path, "version = 1\ntick_interval_ms = 500\n").unwrap();
+
+        let (mut config, _) =
+            Config::load_or_create_from(Some(&path), &Machine::default()).unwrap();
+        let content = "version = 1\ntick_interval_ms = \"fast\"\n";
+        std::fs::write(&path, content).unwrap();
+
+        assert!(config.set_tick_interval(2000).is_err());
+        assert_eq!(config.tick_interval_ms, 2000);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reload_takes_changed_values_from_the_file() {
+        let path = temp_path("reload-changes");
+        std::fs::write(&path, "version = 1\n").unwrap();
+
+        let (mut config, _) =
+            Config::load_or_create_from(Some(&path), &Machine::default()).unwrap();
+        config
+            .set_overrides(
+                "command line",
+                toml::from_str("theme = \"dracula\"").unwrap(),
+            )
+            .unwrap();
+        config.set_theme(ThemeVariant::GameBoy).unwrap();
+        config.set_tick_interval(2000).unwrap();
+
+        let reloaded = config.reload(&Machine::default()).unwrap();
+        assert_eq!(reloaded.theme, ThemeVariant::GameBoy);
+        assert_eq!(reloaded.source("theme"), path.display().to_string());
+
+        std::fs::write(&path, "version = 1\ntick_interval_ms = 800\n").unwrap();
+        let reloaded = config.reload(&Machine::default()).unwrap();
+        assert_eq!(reloaded.tick_interval_ms, 800);
+        assert_eq!(reloaded.theme, ThemeVariant::Default);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn changes_are_written_without_the_defaults() {
+        let path = temp_path("changes");
+        let _ = std::fs::remove_file(&path);
+
+        let (mut config, _) =
+            Config::load_or_create_from(Some(&path), &Machine::default()).unwrap();
+        config.set_tick_interval(2000).unwrap();
+        config
+            .remember(|last_applied| last_applied.max_charge_limit = Some(80))
+            .unwrap();
+
+        let table: toml::Table = toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            table,
+            toml::from_str(
+                "version = 1\ntick_interval_ms = 2000\n[last_applied]\nmax_charge_limit = 80"
+            )
+            .unwrap()
+        );
+
+        std::fs::remove_file(&path).unwrap();
     }
 }
@@ -0,0 +1,377 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use framework_lib::smbios;
+use toml::{Table, Value};
+
+use crate::framework::info::{self, FrameworkInfo};
+
+/// Tables whose values only apply on a matching machine, e.g.
+/// `[platform.Framework16Amd7080]` or `[serial."FRANBMCP0123456789"]`
+pub const MACHINE_SECTIONS: [&str; 3] = ["platform", "product", "serial"];
+
+/// Config file provided by the administrator, the user file takes precedence
+pub fn system_config_path() -> Option<PathBuf> {
+    if cfg!(windows) {
+        std::env::var_os("ProgramData").map(|dir| {
+            PathBuf::from(dir)
+                .join("framework-tool-tui")
+                .join("config.toml")
+        })
+    } else {
+        Some(PathBuf::from("/etc/framework-tool-tui/config.toml"))
+    }
+}
+
+/// Identity of the machine the machine sections are matched against
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Machine {
+    /// Name of the `Platform` variant, e.g. `Framework13Amd7080`
+    pub platform: Option<String>,
+    /// SMBIOS product name, e.g. `Laptop 16 (AMD Ryzen 7040 Series)`
+    pub product: Option<String>,
+    /// SMBIOS serial number
+    pub serial: Option<String>,
+}
+
+impl Machine {
+    pub fn from_info(info: &FrameworkInfo) -> Self {
+        Self {
+            platform: info.platform.map(|platform| format!("{:?}", platform)),
+            product: info.smbios_product.clone(),
+            serial: info.smbios_serial.clone(),
+        }
+    }
+
+    /// Read the SMBIOS tables directly, on Linux this needs root
+    pub fn detect() -> Self {
+        let info = FrameworkInfo {
+            platform: smbios::get_platform(),
+            smbios_serial: info::smbios_serial(&smbios::get_smbios()),
+            ..FrameworkInfo::default()
+        };
+
+        Self {
+            product: smbios::get_product_name(),
+            ..Self::from_info(&info)
+        }
+    }
+
+    fn key(&self, section: &str) -> Option<&str> {
+        match section {
+            "platform" => self.platform.as_deref(),
+            "product" => self.product.as_deref(),
+            "serial" => self.serial.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Parsed config file, layers later in the list take precedence
+pub struct Layer {
+    pub name: String,
+    pub table: Table,
+}
+
+/// Merge the layers and the machine sections matching `machine`, in this
+/// order for every layer: the top level values, `platform`, `product` and
+/// `serial`. The source of every merged value is returned by its dotted key.
+pub fn merge(layers: &[Layer], machine: &Machine) -> (Table, BTreeMap<String, String>) {
+    let mut merged = Table::new();
+    let mut sources = BTreeMap::new();
+
+    for layer in layers {
+        let mut base = layer.table.clone();
+        base.remove("version");
+        for section in MACHINE_SECTIONS {
+            base.remove(section);
+        }
+        merge_table(&mut merged, base, "", &layer.name, &mut sources);
+
+        for section in MACHINE_SECTIONS {
+            let Some(key) = machine.key(section) else {
+                continue;
+            };
+
+            if let Some(Value::Table(values)) = layer.table.get(section).and_then(|s| s.get(key)) {
+                let source = format!("{} [{}.\"{}\"]", layer.name, section, key);
+
+                merge_table(&mut merged, values.clone(), "", &source, &mut sources);
+            }
+        }
+    }
+
+    (merged, sources)
+}
+
+/// Merge `from` into `into`, recording `source` for every value taken from it
+pub fn merge_table(
+    into: &mut Table,
+    from: Table,
+    prefix: &str,
+    source: &str,
+    sources: &mut BTreeMap<String, String>,
+) {
+    for (key, value) in from {
+        let path = dotted(prefix, &key);
+
+        match value {
+            Value::Table(from) => {
+                if !matches!(into.get(&key), Some(Value::Table(_))) {
+                    forget(sources, &path);
+                    into.insert(key.clone(), Value::Table(Table::new()));
+                }
+                if let Some(Value::Table(into)) = into.get_mut(&key) {
+                    merge_table(into, from, &path, source, sources);
+                }
+            }
+            value => {
+                forget(sources, &path);
+                sources.insert(path, source.to_string());
+                into.insert(key, value);
+            }
+        }
+    }
+}
+
+fn forget(sources: &mut BTreeMap<String, String>, path: &str) {
+    let nested = format!("{}.", path);
+
+    sources.retain(|key, _| key != path && !key.starts_with(&nested));
+}
+
+/// Write the values that differ between `before` and `after` into `table`,
+/// leaving everything else in it as it is
+pub fn write_changes(table: &mut Table, before: &Table, after: &Table) {
+    for (path, value) in changes(before, after, &[]) {
+        set(table, &path, value);
+    }
+}
+
+/// Remove the values that differ between `before` and `after` from `table`
+pub fn remove_changes(table: &mut Table, before: &Table, after: &Table) {
+    for (path, _) in changes(before, after, &[]) {
+        set(table, &path, None);
+    }
+}
+
+/// Same as [`write_changes`], but a value the file sets in a section matching
+/// `machine` is changed in that section, where it takes effect
+pub fn write_machine_changes(table: &mut Table, machine: &Machine, before: &Table, after: &Table) {
+    for (path, value) in changes(before, after, &[]) {
+        // NOTE: the section merged last wins, so it's the one to change
+        let section = MACHINE_SECTIONS
+            .iter()
+            .rev()
+            .filter_map(|section| Some([section.to_string(), machine.key(section)?.to_string()]))
+            .map(|section| [section.as_slice(), &path].concat())
+            .find(|section_path| get(table, section_path).is_some());
+
+        set(table, &section.unwrap_or(path), value);
+    }
+}
+
+/// Values that differ between `before` and `after` by their path under
+/// `prefix`, `None` for the values `after` no longer has
+fn changes(before: &Table, after: &Table, prefix: &[String]) -> Vec<(Vec<String>, Option<Value>)> {
+    let mut changes = Vec::new();
+
+    for (key, value) in after {
+        let path = [prefix, std::slice::from_ref(key)].concat();
+
+        match (before.get(key), value) {
+            (Some(Value::Table(before)), Value::Table(after)) => {
+                changes.extend(self::changes(before, after, &path));
+            }
+            (before, value) if before != Some(value) => changes.push((path, Some(value.clone()))),
+            _ => {}
+        }
+    }
+    for key in before.keys().filter(|key| !after.contains_key(*key)) {
+        changes.push(([prefix, std::slice::from_ref(key)].concat(), None));
+    }
+
+    changes
+}
+
+fn get<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+    let (key, parents) = path.split_last()?;
+
+    parents
+        .iter()
+        .try_fold(table, |table, parent| table.get(parent)?.as_table())?
+        .get(key)
+}
+
+/// Set or, for `None`, remove the value at `path`, adding the missing tables on the way
+fn set(table: &mut Table, path: &[String], value: Option<Value>) {
+    let Some((key, rest)) = path.split_first() else {
+        return;
+    };
+
+    if rest.is_empty() {
+        match value {
+            Some(value) => table.insert(key.clone(), value),
+            None => table.remove(key),
+        };
+        return;
+    }
+
+    if !matches!(table.get(key), Some(Value::Table(_))) {
+        if value.is_none() {
+            return;
+        }
+        table.insert(key.clone(), Value::Table(Table::new()));
+    }
+    if let Some(Value::Table(nested)) = table.get_mut(key) {
+        set(nested, rest, value);
+    }
+}
+
+/// Every value in `table` that isn't a table itself, by its dotted key
+pub fn flatten(table: &Table) -> Vec<(String, Value)> {
+    fn walk(table: &Table, prefix: &str, values: &mut Vec<(String, Value)>) {
+        for (key, value) in table {
+            let path = dotted(prefix, key);
+
+            match value {
+                Value::Table(table) => walk(table, &path, values),
+                value => values.push((path, value.clone())),
+            }
+        }
+    }
+
+    let mut values = Vec::new();
+    walk(table, "", &mut values);
+
+    values
+}
+
+fn dotted(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::layer::{merge, write_changes, write_machine_changes, Layer, Machine};
+
+    fn layer(name: &str, content: &str) -> Layer {
+        Layer {
+            name: name.to_string(),
+            table: toml::from_str(content).unwrap(),
+        }
+    }
+
+    #[test]
+    fn later_layers_and_matching_sections_win() {
+        let layers = [
+            layer(
+                "system",
+                r#"
+                theme = "dracula"
+                tick_interval_ms = 2000
+
+                [alerts]
+                charge_below = 20
+
+                [platform.Framework16Amd7080]
+                tick_interval_ms = 500
+                "#,
+            ),
+            layer(
+                "user",
+                r#"
+                theme = "gameboy"
+
+                [platform.Framework13Amd7080]
+                theme = "alucard"
+
+                [serial.FRA123]
+                alerts = { bell = false }
+                "#,
+            ),
+        ];
+        let machine = Machine {
+            platform: Some("Framework16Amd7080".to_string()),
+            serial: Some("FRA123".to_string()),
+            ..Machine::default()
+        };
+
+        let (merged, sources) = merge(&layers, &machine);
+
+        assert_eq!(merged["theme"].as_str(), Some("gameboy"));
+        assert_eq!(merged["tick_interval_ms"].as_integer(), Some(500));
+        assert_eq!(merged["alerts"]["charge_below"].as_integer(), Some(20));
+        assert_eq!(merged["alerts"]["bell"].as_bool(), Some(false));
+        assert_eq!(sources["theme"], "user");
+        assert_eq!(
+            sources["tick_interval_ms"],
+            "system [platform.\"Framework16Amd7080\"]"
+        );
+        assert_eq!(sources["alerts.bell"], "user [serial.\"FRA123\"]");
+    }
+
+    #[test]
+    fn only_changed_values_are_written() {
+        let mut table: toml::Table = toml::from_str("[platform.X]\ntheme = \"dracula\"").unwrap();
+        let before = toml::from_str("theme = \"gameboy\"\n[alerts]\nbell = true").unwrap();
+        let after = toml::from_str("theme = \"gameboy\"\n[alerts]\nbell = false").unwrap();
+
+        write_changes(&mut table, &before, &after);
+
+        assert_eq!(
+            table,
+            toml::from_str("[platform.X]\ntheme = \"dracula\"\n[alerts]\nbell = false").unwrap()
+        );
+    }
+
+    #[test]
+    fn values_set_for_this_machine_are_changed_there() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+            theme = "gameboy"
+
+            [platform.Framework16Amd7080]
+            theme = "dracula"
+
+            [serial.FRA123.alerts]
+            bell = true
+            "#,
+        )
+        .unwrap();
+        let before =
+            toml::from_str("theme = \"dracula\"\ntick_interval_ms = 1000\n[alerts]\nbell = true")
+                .unwrap();
+        let after =
+            toml::from_str("theme = \"alucard\"\ntick_interval_ms = 500\n[alerts]\nbell = false")
+                .unwrap();
+        let machine = Machine {
+            platform: Some("Framework16Amd7080".to_string()),
+            serial: Some("FRA123".to_string()),
+            ..Machine::default()
+        };
+
+        write_machine_changes(&mut table, &machine, &before, &after);
+
+        assert_eq!(
+            table,
+            toml::from_str(
+                r#"
+                theme = "gameboy"
+                tick_interval_ms = 500
+
+                [platform.Framework16Amd7080]
+                theme = "alucard"
+
+                [serial.FRA123.alerts]
+                bell = false
+                "#,
+            )
+            .unwrap()
+        );
+    }
+}
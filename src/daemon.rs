@@ -0,0 +1,488 @@
+use std::{fs, os::unix::fs::PermissionsExt, path::Path, time::Duration};
+
+use color_eyre::eyre::Report;
+use framework_lib::chromium_ec::{EcError, EcResponseStatus};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    signal::unix::{signal, SignalKind},
+    sync::{mpsc, oneshot, watch},
+};
+
+use crate::{
+    config::{layer::Machine, Config},
+    daemon::protocol::{Command, RemoteError, Request, Response, PROTOCOL_VERSION},
+    framework::{
+        fingerprint::FpLedBrightnessCapability, info::FrameworkInfo, EcErrorWrapper, Framework,
+        Hardware, MAX_CHARGE_LIMIT_MIN,
+    },
+    last_applied::{self, LastApplied},
+};
+
+pub mod client;
+pub mod dbus;
+pub mod protocol;
+
+pub const DEFAULT_SOCKET_PATH: &str = "/run/framework-tool-tui.sock";
+pub const DEFAULT_SOCKET_GROUP: &str = "framework";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+type CommandRequest = (Command, oneshot::Sender<Response>);
+
+/// Privileged process that owns the EC and serves unprivileged TUI clients
+/// over a Unix socket.
+pub struct Daemon {
+    framework: Framework,
+}
+
+/// Shared state handed to every client connection
+#[derive(Clone)]
+struct DaemonHandle {
+    info_rx: watch::Receiver<FrameworkInfo>,
+    command_tx: mpsc::UnboundedSender<CommandRequest>,
+    fp_led_brightness_capability: FpLedBrightnessCapability,
+}
+
+impl Daemon {
+    pub fn new() -> color_eyre::Result<Self> {
+        Ok(Self {
+            framework: Framework::open()?,
+        })
+    }
+
+    /// Identity of this machine for the machine sections of the config
+    pub fn machine(&mut self) -> Machine {
+        Machine::from_info(&self.framework.get_info())
+    }
+
+    pub async fn run(
+        mut self,
+        socket_path: &Path,
+        group: &str,
+        dbus_bus: Option<dbus::Bus>,
+        mut config: Config,
+    ) -> color_eyre::Result<()> {
+        let listener = bind_socket(socket_path, group)?;
+
+        // NOTE: the EC may have lost the settings over a power cycle or a BIOS update,
+        // when writing them fails clients can still fix them, so keep serving
+        if config.reapply_on_start && !config.last_applied.is_empty() {
+            let fingerprint = self.framework.fingerprint();
+
+            match config
+                .last_applied
+                .reapply(&mut self.framework, &fingerprint)
+            {
+                Ok(corrections) => eprintln!("{}", last_applied::report(&corrections)),
+                Err(error) => eprintln!("Couldn't restore the last applied settings: {}", error),
+            }
+        }
+
+        let (info_tx, info_rx) = watch::channel(self.framework.get_info());
+        let (command_tx, mut command_rx) = mpsc::unbounded_channel::<CommandRequest>();
+        let handle = DaemonHandle {
+            info_rx,
+            command_tx,
+            fp_led_brightness_capability: self.framework.fp_led_brightness_capability(),
+        };
+
+        // NOTE: keep the connection alive for as long as the daemon runs
+        let _dbus_connection = match dbus_bus {
+            Some(bus) => {
+                let connection = dbus::connect(bus).await?;
+                dbus::spawn(connection.clone(), handle.clone());
+
+                eprintln!("Exported {} on the {:?} bus", dbus::BUS_NAME, bus);
+
+                Some(connection)
+            }
+            None => None,
+        };
+
+        tokio::spawn(serve(listener, handle));
+
+        eprintln!(
+            "Listening on {} for members of group '{}'",
+            socket_path.display(),
+            group
+        );
+
+        let mut tick = tokio::time::interval(POLL_INTERVAL);
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut hangup = signal(SignalKind::hangup())?;
+        let machine = self.machine();
+
+        loop {
+            tokio::select! {
+                _ = tick.tick() => {
+                    info_tx.send_replace(self.framework.get_info());
+                }
+                Some((command, reply)) = command_rx.recv() => {
+                    let response = execute(&mut self.framework, command);
+
+                    if matches!(response, Response::Done) {
+                        info_tx.send_modify(|info| apply_command(info, command));
+
+                        if let Err(error) = config.remember(|last_applied| remember_command(last_applied, command)) {
+                            eprintln!("Couldn't save the last applied settings: {}", error);
+                        }
+                    }
+
+                    let _ = reply.send(response);
+                }
+                _ = tokio::signal::ctrl_c() => break,
+                _ = terminate.recv() => break,
+                _ = hangup.recv() => match config.reload(&machine) {
+                    Ok(reloaded) => {
+                        config = reloaded;
+                        eprintln!("Config reloaded");
+                    }
+                    Err(error) => eprintln!("Config not reloaded: {}", error),
+                },
+            }
+        }
+
+        let _ = fs::remove_file(socket_path);
+
+        Ok(())
+    }
+}
+
+/// Why `command` can't be sent to the EC. Clients of the socket and of D-Bus
+/// are checked alike, they may be any member of the group.
+pub fn validate(command: Command) -> Result<(), String> {
+    let percentages = match command {
+        Command::SetMaxChargeLimit(value) => vec![("max charge limit", value)],
+        Command::SetFingerprintBrightness(percentage) => {
+            vec![("fingerprint brightness", percentage)]
+        }
+        Command::SetKeyboardBrightness(percentage) => vec![("keyboard brightness", percentage)],
+        Command::SetChargeLimits(min, max) => {
+            vec![("min charge limit", min), ("max charge limit", max)]
+        }
+        Command::SetFanDuty(percentage) => percentage
+            .map(|percentage| ("fan duty", percentage))
+            .into_iter()
+            .collect(),
+    };
+
+    for (name, value) in percentages {
+        if value > 100 {
+            return Err(format!(
+                "Expected a {} between 0 and 100, got {}",
+                name, value
+            ));
+        }
+    }
+    match command {
+        Command::SetMaxChargeLimit(max) | Command::SetChargeLimits(_, max)
+            if max < MAX_CHARGE_LIMIT_MIN =>
+        {
+            Err(format!(
+                "Expected a max charge limit of at least {}, got {}",
+                MAX_CHARGE_LIMIT_MIN, max
+            ))
+        }
+        Command::SetChargeLimits(min, max) if min > max => Err(format!(
+            "Min charge limit {} is above the max charge limit {}",
+            min, max
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Validate `command` and send it to `hardware`
+fn execute(hardware: &mut dyn Hardware, command: Command) -> Response {
+    if let Err(message) = validate(command) {
+        return Response::Error(RemoteError::Other(message));
+    }
+
+    let result = match command {
+        Command::SetMaxChargeLimit(value) => hardware.set_max_charge_limit(value),
+        Command::SetFingerprintBrightness(percentage) => hardware.set_fp_brightness(percentage),
+        Command::SetKeyboardBrightness(percentage) => hardware.set_kb_brightness(percentage),
+        Command::SetChargeLimits(min, max) => hardware.set_charge_limits(min, max),
+        Command::SetFanDuty(percentage) => hardware.set_fan_duty(percentage),
+    };
+
+    match result {
+        Ok(()) => Response::Done,
+        Err(report) => Response::Error(remote_error(report)),
+    }
+}
+
+fn bind_socket(socket_path: &Path, group: &str) -> color_eyre::Result<UnixListener> {
+    if socket_path.exists() {
+        if std::os::unix::net::UnixStream::connect(socket_path).is_ok() {
+            return Err(Report::msg(format!(
+                "Another daemon is already listening on {}",
+                socket_path.display()
+            )));
+        }
+
+        // NOTE: leftover from a daemon that didn't shut down cleanly
+        fs::remove_file(socket_path)?;
+    }
+
+    let gid = uzers::get_group_by_name(group)
+        .ok_or_else(|| {
+            Report::msg(format!(
+                "Group '{}' doesn't exist. Create it and add the users allowed to control the hardware.",
+                group
+            ))
+        })?
+        .gid();
+
+    let listener = UnixListener::bind(socket_path)?;
+    std::os::unix::fs::chown(socket_path, None, Some(gid))?;
+    fs::set_permissions(socket_path, fs::Permissions::from_mode(0o660))?;
+
+    Ok(listener)
+}
+
+async fn serve(listener: UnixListener, handle: DaemonHandle) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let handle = handle.clone();
+
+                tokio::spawn(async move {
+                    if let Err(error) = handle_connection(stream, handle).await {
+                        eprintln!("Client connection failed: {}", error);
+                    }
+                });
+            }
+            Err(error) => eprintln!("Failed to accept a client connection: {}", error),
+        }
+    }
+}
+
+async fn handle_connection(stream: UnixStream, handle: DaemonHandle) -> color_eyre::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Hello) => Response::Hello {
+                protocol_version: PROTOCOL_VERSION,
+                fp_led_brightness_capability: handle.fp_led_brightness_capability,
+            },
+            Ok(Request::GetInfo) => Response::Info(Box::new(handle.info_rx.borrow().clone())),
+            Ok(Request::Command(command)) => {
+                let (reply_tx, reply_rx) = oneshot::channel();
+
+                handle
+                    .command_tx
+                    .send((command, reply_tx))
+                    .map_err(|_| Report::msg("Daemon is shutting down"))?;
+
+                reply_rx
+                    .await
+                    .map_err(|_| Report::msg("Daemon is shutting down"))?
+            }
+            Err(error) => {
+                Response::Error(RemoteError::Other(format!("Malformed request: {}", error)))
+            }
+        };
+
+        let mut payload = serde_json::to_string(&response)?;
+        payload.push('\n');
+        writer.write_all(payload.as_bytes()).await?;
+    }
+
+    Ok(())
+}
+
+fn remote_error(report: Report) -> RemoteError {
+    match report.downcast::<EcErrorWrapper>() {
+        Ok(EcErrorWrapper(EcError::Response(EcResponseStatus::InvalidVersion))) => {
+            RemoteError::InvalidVersion
+        }
+        Ok(error) => RemoteError::Other(error.to_string()),
+        Err(report) => RemoteError::Other(report.to_string()),
+    }
+}
+
+fn remember_command(last_applied: &mut LastApplied, command: Command) {
+    match command {
+        Command::SetMaxChargeLimit(value) => {
+            last_applied.max_charge_limit = Some(value);
+            last_applied.min_charge_limit = None;
+        }
+        Command::SetFingerprintBrightness(percentage) => {
+            last_applied.fingerprint_brightness = Some(percentage)
+        }
+        Command::SetKeyboardBrightness(percentage) => {
+            last_applied.keyboard_brightness = Some(percentage)
+        }
+        Command::SetChargeLimits(min, max) => {
+            last_applied.min_charge_limit = Some(min);
+            last_applied.max_charge_limit = Some(max);
+        }
+        Command::SetFanDuty(_) => {}
+    }
+}
+
+fn apply_command(info: &mut FrameworkInfo, command: Command) {
+    match command {
+        Command::SetMaxChargeLimit(value) => info.max_charge_limit = Some(value),
+        Command::SetFingerprintBrightness(percentage) => {
+            info.fp_brightness_percentage = Some(percentage)
+        }
+        Command::SetKeyboardBrightness(percentage) => {
+            info.kb_brightness_percentage = Some(percentage)
+        }
+        Command::SetChargeLimits(min, max) => {
+            info.min_charge_limit = Some(min);
+            info.max_charge_limit = Some(max);
+        }
+        // NOTE: the duty cycle isn't part of the snapshot, the next poll shows the new RPM
+        Command::SetFanDuty(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tokio::{
+        net::UnixListener,
+        sync::{mpsc, watch},
+    };
+
+    use crate::{
+        daemon::{
+            client::Client,
+            execute,
+            protocol::{Command, Response},
+            serve, DaemonHandle,
+        },
+        framework::{fingerprint::FpLedBrightnessCapability, info::FrameworkInfo, Hardware},
+    };
+
+    /// Hardware that records the commands reaching it
+    #[derive(Default)]
+    struct Recorder(Vec<Command>);
+
+    impl Hardware for Recorder {
+        fn get_info(&mut self) -> color_eyre::Result<FrameworkInfo> {
+            Ok(FrameworkInfo::default())
+        }
+
+        fn set_max_charge_limit(&mut self, value: u8) -> color_eyre::Result<()> {
+            self.0.push(Command::SetMaxChargeLimit(value));
+            Ok(())
+        }
+
+        fn set_fp_brightness(&mut self, percentage: u8) -> color_eyre::Result<()> {
+            self.0.push(Command::SetFingerprintBrightness(percentage));
+            Ok(())
+        }
+
+        fn set_kb_brightness(&mut self, percentage: u8) -> color_eyre::Result<()> {
+            self.0.push(Command::SetKeyboardBrightness(percentage));
+            Ok(())
+        }
+
+        fn set_charge_limits(&mut self, min: u8, max: u8) -> color_eyre::Result<()> {
+            self.0.push(Command::SetChargeLimits(min, max));
+            Ok(())
+        }
+
+        fn set_fan_duty(&mut self, percentage: Option<u8>) -> color_eyre::Result<()> {
+            self.0.push(Command::SetFanDuty(percentage));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn client_receives_snapshots_and_forwards_commands() {
+        let socket_path = std::env::temp_dir().join(format!(
+            "framework-tool-tui-test-{}.sock",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&socket_path);
+
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let info = FrameworkInfo {
+            charge_percentage: Some(77),
+            ..FrameworkInfo::default()
+        };
+        let (_info_tx, info_rx) = watch::channel(info);
+        let (command_tx, mut command_rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(serve(
+            listener,
+            DaemonHandle {
+                info_rx,
+                command_tx,
+                fp_led_brightness_capability: FpLedBrightnessCapability::Level,
+            },
+        ));
+        let commands = tokio::spawn(async move {
+            let (command, reply) = command_rx.recv().await.unwrap();
+            let _ = reply.send(Response::Done);
+
+            command
+        });
+
+        let path = socket_path.clone();
+        let (capability, charge_percentage) = tokio::task::spawn_blocking(move || {
+            let mut client = Client::connect(&path).unwrap();
+            let info = client.get_info().unwrap();
+
+            client.set_max_charge_limit(90).unwrap();
+
+            (
+                client.fingerprint().led_brightness_capability,
+                info.charge_percentage,
+            )
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(capability, FpLedBrightnessCapability::Level);
+        assert_eq!(charge_percentage, Some(77));
+        assert_eq!(commands.await.unwrap(), Command::SetMaxChargeLimit(90));
+
+        let _ = fs::remove_file(socket_path);
+    }
+
+    #[test]
+    fn rejected_commands_never_reach_the_hardware() {
+        let mut hardware = Recorder::default();
+
+        for command in [
+            Command::SetMaxChargeLimit(0),
+            Command::SetMaxChargeLimit(24),
+            Command::SetMaxChargeLimit(255),
+            Command::SetChargeLimits(80, 60),
+            Command::SetChargeLimits(10, 20),
+            Command::SetKeyboardBrightness(150),
+            Command::SetFingerprintBrightness(101),
+            Command::SetFanDuty(Some(200)),
+        ] {
+            assert!(matches!(
+                execute(&mut hardware, command),
+                Response::Error(_)
+            ));
+        }
+        assert!(hardware.0.is_empty());
+
+        assert!(matches!(
+            execute(&mut hardware, Command::SetChargeLimits(50, 80)),
+            Response::Done
+        ));
+        assert_eq!(hardware.0, vec![Command::SetChargeLimits(50, 80)]);
+    }
+
+    #[test]
+    fn apply_command_updates_snapshot() {
+        let mut info = FrameworkInfo::default();
+
+        super::apply_command(&mut info, Command::SetKeyboardBrightness(30));
+
+        assert_eq!(info.kb_brightness_percentage, Some(30));
+    }
+}
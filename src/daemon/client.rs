@@ -0,0 +1,131 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+    time::Duration,
+};
+
+use color_eyre::eyre::{Context, Report};
+use framework_lib::chromium_ec::{EcError, EcResponseStatus};
+
+use crate::{
+    daemon::protocol::{Command, RemoteError, Request, Response, PROTOCOL_VERSION},
+    framework::{
+        fingerprint::{Fingerprint, FpLedBrightnessCapability},
+        info::FrameworkInfo,
+        EcErrorWrapper, Hardware,
+    },
+};
+
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Unprivileged side of the daemon connection
+pub struct Client {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+    fp_led_brightness_capability: FpLedBrightnessCapability,
+}
+
+impl Client {
+    pub fn connect(socket_path: &Path) -> color_eyre::Result<Self> {
+        let stream = UnixStream::connect(socket_path).wrap_err_with(|| {
+            format!(
+                "Couldn't connect to the daemon at {}. Is it running and are you in its group?",
+                socket_path.display()
+            )
+        })?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+        let mut client = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            fp_led_brightness_capability: FpLedBrightnessCapability::Percentage,
+        };
+
+        match client.request(&Request::Hello)? {
+            Response::Hello {
+                protocol_version,
+                fp_led_brightness_capability,
+            } if protocol_version == PROTOCOL_VERSION => {
+                client.fp_led_brightness_capability = fp_led_brightness_capability;
+
+                Ok(client)
+            }
+            Response::Hello {
+                protocol_version, ..
+            } => Err(Report::msg(format!(
+                "Daemon speaks protocol version {}, but this client expects {}",
+                protocol_version, PROTOCOL_VERSION
+            ))),
+            _ => Err(Report::msg("Unexpected handshake response from the daemon")),
+        }
+    }
+
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint {
+            led_brightness_capability: self.fp_led_brightness_capability,
+        }
+    }
+
+    fn request(&mut self, request: &Request) -> color_eyre::Result<Response> {
+        let mut payload = serde_json::to_string(request)?;
+        payload.push('\n');
+        self.writer
+            .write_all(payload.as_bytes())
+            .wrap_err("Lost connection to the daemon")?;
+
+        let mut line = String::new();
+        if self
+            .reader
+            .read_line(&mut line)
+            .wrap_err("Lost connection to the daemon")?
+            == 0
+        {
+            return Err(Report::msg("The daemon closed the connection"));
+        }
+
+        Ok(serde_json::from_str(&line)?)
+    }
+
+    fn command(&mut self, command: Command) -> color_eyre::Result<()> {
+        match self.request(&Request::Command(command))? {
+            Response::Done => Ok(()),
+            Response::Error(RemoteError::InvalidVersion) => Err(Report::from(EcErrorWrapper(
+                EcError::Response(EcResponseStatus::InvalidVersion),
+            ))),
+            Response::Error(error) => Err(Report::msg(error.to_string())),
+            _ => Err(Report::msg("Unexpected response from the daemon")),
+        }
+    }
+}
+
+impl Hardware for Client {
+    fn get_info(&mut self) -> color_eyre::Result<FrameworkInfo> {
+        match self.request(&Request::GetInfo)? {
+            Response::Info(info) => Ok(*info),
+            Response::Error(error) => Err(Report::msg(error.to_string())),
+            _ => Err(Report::msg("Unexpected response from the daemon")),
+        }
+    }
+
+    fn set_max_charge_limit(&mut self, value: u8) -> color_eyre::Result<()> {
+        self.command(Command::SetMaxChargeLimit(value))
+    }
+
+    fn set_fp_brightness(&mut self, percentage: u8) -> color_eyre::Result<()> {
+        self.command(Command::SetFingerprintBrightness(percentage))
+    }
+
+    fn set_kb_brightness(&mut self, percentage: u8) -> color_eyre::Result<()> {
+        self.command(Command::SetKeyboardBrightness(percentage))
+    }
+
+    fn set_charge_limits(&mut self, min: u8, max: u8) -> color_eyre::Result<()> {
+        self.command(Command::SetChargeLimits(min, max))
+    }
+
+    fn set_fan_duty(&mut self, percentage: Option<u8>) -> color_eyre::Result<()> {
+        self.command(Command::SetFanDuty(percentage))
+    }
+}
@@ -0,0 +1,337 @@
+use clap::ValueEnum;
+use tokio::sync::{mpsc, oneshot, watch};
+use zbus::{fdo, interface, object_server::SignalEmitter, Connection};
+
+use crate::{
+    daemon::{
+        self,
+        protocol::{Command, Response},
+        CommandRequest, DaemonHandle,
+    },
+    framework::info::FrameworkInfo,
+};
+
+pub const BUS_NAME: &str = "com.github.grouzen.FrameworkToolTui";
+pub const OBJECT_PATH: &str = "/com/github/grouzen/FrameworkToolTui";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Bus {
+    System,
+    Session,
+}
+
+/// `com.github.grouzen.FrameworkToolTui1` interface.
+///
+/// Values that the EC didn't report are exposed as `0` (or an empty string/array).
+pub struct FrameworkService {
+    info_rx: watch::Receiver<FrameworkInfo>,
+    command_tx: mpsc::UnboundedSender<CommandRequest>,
+}
+
+#[interface(name = "com.github.grouzen.FrameworkToolTui1")]
+impl FrameworkService {
+    async fn set_max_charge_limit(&self, value: u8) -> fdo::Result<()> {
+        self.execute(Command::SetMaxChargeLimit(value)).await
+    }
+
+    async fn set_keyboard_brightness(&self, percentage: u8) -> fdo::Result<()> {
+        self.execute(Command::SetKeyboardBrightness(percentage))
+            .await
+    }
+
+    async fn set_fingerprint_brightness(&self, percentage: u8) -> fdo::Result<()> {
+        self.execute(Command::SetFingerprintBrightness(percentage))
+            .await
+    }
+
+    #[zbus(property)]
+    fn charge_percentage(&self) -> u32 {
+        self.info_rx.borrow().charge_percentage.unwrap_or(0)
+    }
+
+    #[zbus(property)]
+    fn charging_status(&self) -> String {
+        self.info_rx.borrow().charging_status.clone()
+    }
+
+    #[zbus(property)]
+    fn is_charging(&self) -> bool {
+        self.info_rx.borrow().is_charging
+    }
+
+    #[zbus(property)]
+    fn is_ac_connected(&self) -> bool {
+        self.info_rx.borrow().is_ac_connected
+    }
+
+    #[zbus(property)]
+    fn max_charge_limit(&self) -> u8 {
+        self.info_rx.borrow().max_charge_limit.unwrap_or(0)
+    }
+
+    #[zbus(property)]
+    fn keyboard_brightness(&self) -> u8 {
+        self.info_rx.borrow().kb_brightness_percentage.unwrap_or(0)
+    }
+
+    #[zbus(property)]
+    fn fingerprint_brightness(&self) -> u8 {
+        self.info_rx.borrow().fp_brightness_percentage.unwrap_or(0)
+    }
+
+    #[zbus(property)]
+    fn fan_rpm(&self) -> Vec<u16> {
+        self.info_rx.borrow().fan_rpm.clone().unwrap_or_default()
+    }
+}
+
+impl FrameworkService {
+    /// Validate `command` like the socket does and send it to the daemon
+    async fn execute(&self, command: Command) -> fdo::Result<()> {
+        daemon::validate(command).map_err(fdo::Error::InvalidArgs)?;
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        self.command_tx
+            .send((command, reply_tx))
+            .map_err(|_| fdo::Error::Failed("Daemon is shutting down".to_string()))?;
+
+        match reply_rx.await {
+            Ok(Response::Done) => Ok(()),
+            Ok(Response::Error(error)) => Err(fdo::Error::Failed(error.to_string())),
+            Ok(_) => Err(fdo::Error::Failed("Unexpected daemon response".to_string())),
+            Err(_) => Err(fdo::Error::Failed("Daemon is shutting down".to_string())),
+        }
+    }
+}
+
+pub async fn connect(bus: Bus) -> color_eyre::Result<Connection> {
+    let connection = match bus {
+        Bus::System => Connection::system().await?,
+        Bus::Session => Connection::session().await?,
+    };
+
+    Ok(connection)
+}
+
+/// Export the service on `connection`, claim [`BUS_NAME`] and keep emitting
+/// `PropertiesChanged` for every new snapshot until the daemon stops.
+async fn serve(connection: Connection, handle: DaemonHandle) -> color_eyre::Result<()> {
+    let mut info_rx = handle.info_rx.clone();
+    let service = FrameworkService {
+        info_rx: handle.info_rx,
+        command_tx: handle.command_tx,
+    };
+
+    connection.object_server().at(OBJECT_PATH, service).await?;
+    connection.request_name(BUS_NAME).await?;
+
+    let iface_ref = connection
+        .object_server()
+        .interface::<_, FrameworkService>(OBJECT_PATH)
+        .await?;
+    let mut previous = info_rx.borrow_and_update().clone();
+
+    while info_rx.changed().await.is_ok() {
+        let current = info_rx.borrow_and_update().clone();
+        let service = iface_ref.get().await;
+
+        emit_changes(&service, iface_ref.signal_emitter(), &previous, &current).await?;
+
+        previous = current;
+    }
+
+    Ok(())
+}
+
+async fn emit_changes(
+    service: &FrameworkService,
+    emitter: &SignalEmitter<'_>,
+    previous: &FrameworkInfo,
+    current: &FrameworkInfo,
+) -> zbus::Result<()> {
+    if previous.charge_percentage != current.charge_percentage {
+        service.charge_percentage_changed(emitter).await?;
+    }
+    if previous.charging_status != current.charging_status {
+        service.charging_status_changed(emitter).await?;
+    }
+    if previous.is_charging != current.is_charging {
+        service.is_charging_changed(emitter).await?;
+    }
+    if previous.is_ac_connected != current.is_ac_connected {
+        service.is_ac_connected_changed(emitter).await?;
+    }
+    if previous.max_charge_limit != current.max_charge_limit {
+        service.max_charge_limit_changed(emitter).await?;
+    }
+    if previous.kb_brightness_percentage != current.kb_brightness_percentage {
+        service.keyboard_brightness_changed(emitter).await?;
+    }
+    if previous.fp_brightness_percentage != current.fp_brightness_percentage {
+        service.fingerprint_brightness_changed(emitter).await?;
+    }
+    if previous.fan_rpm != current.fan_rpm {
+        service.fan_rpm_changed(emitter).await?;
+    }
+
+    Ok(())
+}
+
+pub(super) fn spawn(connection: Connection, handle: DaemonHandle) {
+    tokio::spawn(async move {
+        if let Err(error) = serve(connection, handle).await {
+            eprintln!("D-Bus service stopped: {}", error);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command as Process, Stdio},
+        time::Duration,
+    };
+
+    use futures::StreamExt;
+    use tokio::sync::{mpsc, watch};
+    use zbus::{connection::Builder, fdo::PropertiesProxy, names::InterfaceName, Connection};
+
+    use crate::{
+        daemon::{
+            dbus::{serve, BUS_NAME, OBJECT_PATH},
+            protocol::{Command, Response},
+            DaemonHandle,
+        },
+        framework::{fingerprint::FpLedBrightnessCapability, info::FrameworkInfo},
+    };
+
+    const INTERFACE: &str = "com.github.grouzen.FrameworkToolTui1";
+
+    /// Private session bus that is killed when dropped
+    struct PrivateBus(Child);
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    fn start_private_bus() -> Option<(PrivateBus, String)> {
+        let mut child = Process::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+
+        BufReader::new(child.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+
+        Some((PrivateBus(child), address.trim().to_string()))
+    }
+
+    async fn connect(address: &str) -> Connection {
+        Builder::address(address).unwrap().build().await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs dbus-daemon, run with `cargo test -- --ignored`"]
+    async fn service_exposes_properties_signals_and_methods() {
+        let (_bus, address) = start_private_bus().expect("dbus-daemon should be installed");
+
+        let (info_tx, info_rx) = watch::channel(FrameworkInfo {
+            charge_percentage: Some(64),
+            max_charge_limit: Some(80),
+            ..FrameworkInfo::default()
+        });
+        let (command_tx, mut command_rx) = mpsc::unbounded_channel();
+        let handle = DaemonHandle {
+            info_rx,
+            command_tx,
+            fp_led_brightness_capability: FpLedBrightnessCapability::Percentage,
+        };
+
+        let service_connection = connect(&address).await;
+        tokio::spawn(serve(service_connection, handle));
+
+        let client = connect(&address).await;
+        let properties = PropertiesProxy::builder(&client)
+            .destination(BUS_NAME)
+            .unwrap()
+            .path(OBJECT_PATH)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let interface = InterfaceName::try_from(INTERFACE).unwrap();
+
+        // Wait until the service has claimed its name
+        let mut charge = None;
+        for _ in 0..50 {
+            if let Ok(value) = properties.get(interface.clone(), "ChargePercentage").await {
+                charge = Some(u32::try_from(value).unwrap());
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(charge, Some(64));
+
+        // Methods are forwarded as daemon commands
+        let commands = tokio::spawn(async move {
+            let (command, reply) = command_rx.recv().await.unwrap();
+            let _ = reply.send(Response::Done);
+
+            command
+        });
+        client
+            .call_method(
+                Some(BUS_NAME),
+                OBJECT_PATH,
+                Some(INTERFACE),
+                "SetMaxChargeLimit",
+                &(90u8),
+            )
+            .await
+            .unwrap();
+        assert_eq!(commands.await.unwrap(), Command::SetMaxChargeLimit(90));
+
+        // Out of range values are rejected before reaching the EC
+        assert!(client
+            .call_method(
+                Some(BUS_NAME),
+                OBJECT_PATH,
+                Some(INTERFACE),
+                "SetKeyboardBrightness",
+                &(150u8),
+            )
+            .await
+            .is_err());
+        assert!(client
+            .call_method(
+                Some(BUS_NAME),
+                OBJECT_PATH,
+                Some(INTERFACE),
+                "SetMaxChargeLimit",
+                &(10u8),
+            )
+            .await
+            .is_err());
+
+        // New snapshots emit PropertiesChanged
+        let mut changes = properties.receive_properties_changed().await.unwrap();
+        info_tx.send_modify(|info| info.charge_percentage = Some(65));
+
+        let signal = tokio::time::timeout(Duration::from_secs(5), changes.next())
+            .await
+            .unwrap()
+            .unwrap();
+        let args = signal.args().unwrap();
+
+        assert_eq!(args.interface_name().as_str(), INTERFACE);
+        assert!(args.changed_properties().contains_key("ChargePercentage"));
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use crate::framework::{fingerprint::FpLedBrightnessCapability, info::FrameworkInfo};
+
+/// Bumped whenever a message changes in a backwards incompatible way
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Messages sent by the TUI client, one JSON document per line
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Request {
+    Hello,
+    GetInfo,
+    Command(Command),
+}
+
+/// Hardware mutations the daemon performs on behalf of the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Command {
+    SetMaxChargeLimit(u8),
+    SetFingerprintBrightness(u8),
+    SetKeyboardBrightness(u8),
+    SetChargeLimits(u8, u8),
+    SetFanDuty(Option<u8>),
+}
+
+/// Messages sent by the daemon in reply to a [`Request`], one JSON document per line
+#[derive(Serialize, Deserialize)]
+pub enum Response {
+    Hello {
+        protocol_version: u32,
+        fp_led_brightness_capability: FpLedBrightnessCapability,
+    },
+    Info(Box<FrameworkInfo>),
+    Done,
+    Error(RemoteError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RemoteError {
+    /// The EC doesn't support the command version, usually fixed by a BIOS update
+    InvalidVersion,
+    Other(String),
+}
+
+impl std::fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoteError::InvalidVersion => write!(f, "EC command version is not supported"),
+            RemoteError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        daemon::protocol::{Command, Request, Response},
+        framework::info::FrameworkInfo,
+    };
+
+    #[test]
+    fn request_round_trips_through_json() {
+        let request = Request::Command(Command::SetMaxChargeLimit(80));
+
+        let json = serde_json::to_string(&request).unwrap();
+        let decoded = serde_json::from_str::<Request>(&json).unwrap();
+
+        assert!(matches!(
+            decoded,
+            Request::Command(Command::SetMaxChargeLimit(80))
+        ));
+    }
+
+    #[test]
+    fn info_response_round_trips_through_json() {
+        let info = FrameworkInfo {
+            charge_percentage: Some(42),
+            charging_status: "Charging".to_string(),
+            fan_rpm: Some(vec![2400]),
+            platform: Some(framework_lib::smbios::Platform::Framework13AmdAi300),
+            ..FrameworkInfo::default()
+        };
+
+        let json = serde_json::to_string(&Response::Info(Box::new(info))).unwrap();
+        let decoded = serde_json::from_str::<Response>(&json).unwrap();
+
+        match decoded {
+            Response::Info(info) => {
+                assert_eq!(info.charge_percentage, Some(42));
+                assert_eq!(info.charging_status, "Charging");
+                assert_eq!(info.fan_rpm, Some(vec![2400]));
+                assert_eq!(
+                    info.platform,
+                    Some(framework_lib::smbios::Platform::Framework13AmdAi300)
+                );
+            }
+            _ => panic!("Expected info response"),
+        }
+    }
+}
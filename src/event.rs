@@ -1,13 +1,22 @@
-use std::time::Duration;
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
 
 use color_eyre::eyre::Report;
 use crossterm::event::EventStream;
 use futures::{FutureExt, StreamExt};
 use tokio::sync::{mpsc, watch};
 
+/// How often the watched files are checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
 pub enum Event {
     Tick,
     Input(crossterm::event::Event),
+    /// A watched file changed
+    Reload,
 }
 
 pub struct EventLoop {
@@ -62,6 +71,32 @@ impl EventLoop {
         });
     }
 
+    /// Send [`Event::Reload`] when one of `paths` is created, modified or removed
+    pub fn watch(&self, paths: Vec<PathBuf>) {
+        let tx = self.tx.clone();
+
+        tokio::spawn(async move {
+            let modified = |paths: &[PathBuf]| -> Vec<Option<SystemTime>> {
+                paths
+                    .iter()
+                    .map(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+                    .collect()
+            };
+            let mut last_modified = modified(&paths);
+            let mut poll = tokio::time::interval(WATCH_INTERVAL);
+
+            loop {
+                poll.tick().await;
+
+                let current = modified(&paths);
+                if current != last_modified {
+                    last_modified = current;
+                    let _ = tx.send(Event::Reload);
+                }
+            }
+        });
+    }
+
     pub fn set_tick_interval(&self, tick_interval: Duration) {
         let _ = self.interval_tx.send(tick_interval);
     }
@@ -20,6 +20,28 @@ const EC_FAN_SPEED_ENTRIES: usize = 4;
 /// Used on old EC firmware (before 2023)
 const EC_FAN_SPEED_NOT_PRESENT: u16 = 0xFFFF;
 
+/// Lowest max charge limit that is applied, lower ones aren't recommended for the battery
+pub const MAX_CHARGE_LIMIT_MIN: u8 = 25;
+
+/// Source of hardware telemetry and target of hardware mutations.
+///
+/// Implemented by [`Framework`], which talks to the EC directly, and by the
+/// daemon client, which forwards everything to the privileged daemon.
+pub trait Hardware {
+    fn get_info(&mut self) -> color_eyre::Result<FrameworkInfo>;
+
+    fn set_max_charge_limit(&mut self, value: u8) -> color_eyre::Result<()>;
+
+    fn set_fp_brightness(&mut self, percentage: u8) -> color_eyre::Result<()>;
+
+    fn set_kb_brightness(&mut self, percentage: u8) -> color_eyre::Result<()>;
+
+    fn set_charge_limits(&mut self, min: u8, max: u8) -> color_eyre::Result<()>;
+
+    /// Fixed duty cycle for all fans, `None` gives control back to the EC
+    fn set_fan_duty(&mut self, percentage: Option<u8>) -> color_eyre::Result<()>;
+}
+
 pub struct Framework {
     ec: CrosEc,
     fingerprint: Arc<Fingerprint>,
@@ -41,12 +63,43 @@ impl Framework {
         Framework { ec, fingerprint }
     }
 
+    /// Open the EC directly, requires root privileges
+    pub fn open() -> color_eyre::Result<Self> {
+        let ec = CrosEc::new();
+        let fingerprint = Arc::new(Fingerprint::new(&ec)?);
+
+        Ok(Self::new(ec, fingerprint))
+    }
+
+    pub fn fingerprint(&self) -> Arc<Fingerprint> {
+        self.fingerprint.clone()
+    }
+
+    pub fn fp_led_brightness_capability(&self) -> FpLedBrightnessCapability {
+        self.fingerprint.led_brightness_capability
+    }
+
     pub fn set_max_charge_limit(&self, value: u8) -> color_eyre::Result<()> {
         self.ec
             .set_charge_limit(0, value)
             .map_err(|error| Report::from(EcErrorWrapper(error)))
     }
 
+    pub fn set_charge_limits(&self, min: u8, max: u8) -> color_eyre::Result<()> {
+        self.ec
+            .set_charge_limit(min, max)
+            .map_err(|error| Report::from(EcErrorWrapper(error)))
+    }
+
+    pub fn set_fan_duty(&self, percentage: Option<u8>) -> color_eyre::Result<()> {
+        let result = match percentage {
+            Some(percentage) => self.ec.fan_set_duty(None, percentage as u32),
+            None => self.ec.autofanctrl(None),
+        };
+
+        result.map_err(|error| Report::from(EcErrorWrapper(error)))
+    }
+
     pub fn set_fp_brightness(&self, percentage: u8) -> color_eyre::Result<()> {
         let result = match self.fingerprint.led_brightness_capability {
             FpLedBrightnessCapability::Level => {
@@ -112,3 +165,31 @@ impl Framework {
         Ok(rpms)
     }
 }
+
+impl Hardware for Framework {
+    fn get_info(&mut self) -> color_eyre::Result<FrameworkInfo> {
+        Ok(Framework::get_info(self))
+    }
+
+    fn set_max_charge_limit(&mut self, value: u8) -> color_eyre::Result<()> {
+        Framework::set_max_charge_limit(self, value)
+    }
+
+    fn set_fp_brightness(&mut self, percentage: u8) -> color_eyre::Result<()> {
+        Framework::set_fp_brightness(self, percentage)
+    }
+
+    fn set_kb_brightness(&mut self, percentage: u8) -> color_eyre::Result<()> {
+        Framework::set_kb_brightness(self, percentage);
+
+        Ok(())
+    }
+
+    fn set_charge_limits(&mut self, min: u8, max: u8) -> color_eyre::Result<()> {
+        Framework::set_charge_limits(self, min, max)
+    }
+
+    fn set_fan_duty(&mut self, percentage: Option<u8>) -> color_eyre::Result<()> {
+        Framework::set_fan_duty(self, percentage)
+    }
+}
@@ -1,5 +1,6 @@
 use color_eyre::eyre::Report;
 use framework_lib::chromium_ec::{commands::FpLedBrightnessLevel, CrosEc};
+use serde::{Deserialize, Serialize};
 
 use crate::framework::EcErrorWrapper;
 
@@ -7,6 +8,7 @@ const FP_LED_BRIGHTNESS_LEVEL_LOW: u8 = 15;
 const FP_LED_BRIGHTNESS_LEVEL_MEDIUM: u8 = 40;
 const FP_LED_BRIGHTNESS_LEVEL_HIGH: u8 = 55;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FpLedBrightnessCapability {
     Level,
     Percentage,
@@ -63,6 +65,17 @@ impl Fingerprint {
             }
         }
     }
+
+    /// Closest brightness the LED supports to a typed `value`
+    pub fn validate_led_brightness(&self, value: u8) -> u8 {
+        match self.led_brightness_capability {
+            FpLedBrightnessCapability::Level => {
+                led_brightness_level_to_percentage(value, led_brightness_percentage_to_level(value))
+            }
+            // NOTE: same lower bound as when adjusting by delta
+            FpLedBrightnessCapability::Percentage => value.clamp(5, 100),
+        }
+    }
 }
 
 pub fn led_brightness_percentage_to_level(value: u8) -> FpLedBrightnessLevel {
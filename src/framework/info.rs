@@ -6,8 +6,12 @@ use framework_lib::power::UsbPdPowerInfo;
 use framework_lib::power::UsbPowerRoles;
 use framework_lib::smbios::Platform;
 use framework_lib::smbios::SmbiosStore;
+use serde::{Deserialize, Serialize};
 
-#[derive(Default)]
+/// Charge level below which the battery is considered low
+pub const LOW_CHARGE_PERCENTAGE: u32 = 15;
+
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct FrameworkInfo {
     pub charge_percentage: Option<u32>,
     pub charger_voltage: Option<u32>,
@@ -19,8 +23,10 @@ pub struct FrameworkInfo {
     pub capacity_loss_per_cycle: Option<f32>,
     pub is_charging: bool,
     pub is_ac_connected: bool,
-    pub charging_status: &'static str,
+    pub charging_status: String,
     pub max_charge_limit: Option<u8>,
+    #[serde(default)]
+    pub min_charge_limit: Option<u8>,
     pub is_microphone_enabled: bool,
     pub is_camera_enabled: bool,
     pub fp_brightness_percentage: Option<u8>,
@@ -29,8 +35,13 @@ pub struct FrameworkInfo {
     pub smbios_version: Option<String>,
     pub smbios_release_date: Option<String>,
     pub smbios_vendor: Option<String>,
+    #[serde(default)]
+    pub smbios_product: Option<String>,
+    #[serde(default)]
+    pub smbios_serial: Option<String>,
     pub pd_ports: PdPortsInfo,
     pub fan_rpm: Option<Vec<u16>>,
+    #[serde(with = "platform_serde")]
     pub platform: Option<Platform>,
 }
 
@@ -58,8 +69,9 @@ impl FrameworkInfo {
             capacity_loss_per_cycle: capacity_loss_per_cycle(power),
             is_charging: is_charging(power),
             is_ac_connected: is_ac_connected(power),
-            charging_status: charging_status(power),
+            charging_status: charging_status(power).to_string(),
             max_charge_limit: max_charge_limit(charge_limit),
+            min_charge_limit: min_charge_limit(charge_limit),
             is_microphone_enabled: is_microphone_enabled(privacy),
             is_camera_enabled: is_camera_enabled(privacy),
             fp_brightness_percentage: fp_brightness_percentage(fp_brightness),
@@ -68,14 +80,28 @@ impl FrameworkInfo {
             smbios_version: smbios_version(smbios),
             smbios_release_date: smbios_release_date(smbios),
             smbios_vendor: smbios_vendor(smbios),
+            smbios_product: smbios_product(smbios),
+            smbios_serial: smbios_serial(smbios),
             pd_ports: pd_ports_info(pd_ports),
             fan_rpm,
             platform,
         }
     }
+
+    pub fn is_charge_low(&self) -> bool {
+        matches!(self.charge_percentage, Some(charge_percentage) if charge_percentage < LOW_CHARGE_PERCENTAGE)
+    }
+
+    /// Power flowing into or out of the battery, in watts
+    pub fn battery_power_watts(&self) -> Option<f32> {
+        match (self.charger_voltage, self.charger_current) {
+            (Some(voltage), Some(current)) => Some(voltage as f32 * current as f32 / 1_000_000.0),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct PdPortsInfo {
     pub left_back: Option<PdPortInfo>,
     pub left_front: Option<PdPortInfo>,
@@ -83,7 +109,7 @@ pub struct PdPortsInfo {
     pub right_front: Option<PdPortInfo>,
 }
 
-#[derive(Default)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PdPortInfo {
     pub role: String,
     pub dualrole: String,
@@ -184,6 +210,10 @@ fn max_charge_limit(charge_limit: &Option<(u8, u8)>) -> Option<u8> {
     charge_limit.as_ref().map(|charge_limit| charge_limit.1)
 }
 
+fn min_charge_limit(charge_limit: &Option<(u8, u8)>) -> Option<u8> {
+    charge_limit.as_ref().map(|charge_limit| charge_limit.0)
+}
+
 fn is_microphone_enabled(privacy: &Option<(bool, bool)>) -> bool {
     privacy.as_ref().map(|privacy| privacy.0).unwrap_or(false)
 }
@@ -241,6 +271,26 @@ fn smbios_vendor(smbios: &Option<SmbiosStore>) -> Option<String> {
     })
 }
 
+fn smbios_product(smbios: &Option<SmbiosStore>) -> Option<String> {
+    smbios.as_ref().and_then(|smbios| {
+        smbios.structures().find_map(|result| match result {
+            Ok(Structure::System(data)) if !data.product.is_empty() => {
+                Some(data.product.to_string())
+            }
+            _ => None,
+        })
+    })
+}
+
+pub fn smbios_serial(smbios: &Option<SmbiosStore>) -> Option<String> {
+    smbios.as_ref().and_then(|smbios| {
+        smbios.structures().find_map(|result| match result {
+            Ok(Structure::System(data)) if !data.serial.is_empty() => Some(data.serial.to_string()),
+            _ => None,
+        })
+    })
+}
+
 fn pd_ports_info(pd_ports: Vec<Option<UsbPdPowerInfo>>) -> PdPortsInfo {
     let left_back = pd_ports
         .get(3)
@@ -302,3 +352,42 @@ fn pd_port_info(pd_port: &UsbPdPowerInfo) -> PdPortInfo {
         current_max: pd_port.meas.current_max,
     }
 }
+
+// NOTE: `Platform` comes from framework_lib and doesn't implement serde traits
+mod platform_serde {
+    use framework_lib::smbios::Platform;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "Platform")]
+    enum PlatformDef {
+        Framework12IntelGen13,
+        IntelGen11,
+        IntelGen12,
+        IntelGen13,
+        IntelCoreUltra1,
+        Framework13Amd7080,
+        Framework13AmdAi300,
+        Framework16Amd7080,
+        Framework16AmdAi300,
+        FrameworkDesktopAmdAiMax300,
+        GenericFramework((u16, u16, u16), (u8, u8, u8)),
+        UnknownSystem,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapper(#[serde(with = "PlatformDef")] Platform);
+
+    pub fn serialize<S: Serializer>(
+        platform: &Option<Platform>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        platform.map(Wrapper).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Platform>, D::Error> {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(platform)| platform))
+    }
+}
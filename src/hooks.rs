@@ -0,0 +1,329 @@
+use std::process::Stdio;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    framework::info::{FrameworkInfo, PdPortInfo},
+    status::on_off,
+};
+
+/// Shell commands run when the hardware state changes, e.g.
+/// `on_ac_disconnected = "systemctl --user stop syncthing"`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub on_ac_connected: Option<String>,
+    pub on_ac_disconnected: Option<String>,
+    pub on_charging_started: Option<String>,
+    pub on_charging_stopped: Option<String>,
+    /// Runs once when the charge drops below `charge_below_percentage`
+    pub on_charge_below: Option<String>,
+    pub charge_below_percentage: u32,
+    pub on_privacy_camera_changed: Option<String>,
+    pub on_privacy_microphone_changed: Option<String>,
+    pub on_pd_port_attached: Option<String>,
+    pub on_pd_port_detached: Option<String>,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_ac_connected: None,
+            on_ac_disconnected: None,
+            on_charging_started: None,
+            on_charging_stopped: None,
+            on_charge_below: None,
+            charge_below_percentage: 20,
+            on_privacy_camera_changed: None,
+            on_privacy_microphone_changed: None,
+            on_pd_port_attached: None,
+            on_pd_port_detached: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookEvent {
+    AcConnected,
+    AcDisconnected,
+    ChargingStarted,
+    ChargingStopped,
+    ChargeBelow {
+        threshold: u32,
+    },
+    PrivacyCameraChanged {
+        enabled: bool,
+    },
+    PrivacyMicrophoneChanged {
+        enabled: bool,
+    },
+    PdPortAttached {
+        port: &'static str,
+        info: PdPortInfo,
+    },
+    PdPortDetached {
+        port: &'static str,
+    },
+}
+
+impl HookEvent {
+    /// Name of the config key, also exported as `FRAMEWORK_EVENT`
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::AcConnected => "on_ac_connected",
+            HookEvent::AcDisconnected => "on_ac_disconnected",
+            HookEvent::ChargingStarted => "on_charging_started",
+            HookEvent::ChargingStopped => "on_charging_stopped",
+            HookEvent::ChargeBelow { .. } => "on_charge_below",
+            HookEvent::PrivacyCameraChanged { .. } => "on_privacy_camera_changed",
+            HookEvent::PrivacyMicrophoneChanged { .. } => "on_privacy_microphone_changed",
+            HookEvent::PdPortAttached { .. } => "on_pd_port_attached",
+            HookEvent::PdPortDetached { .. } => "on_pd_port_detached",
+        }
+    }
+
+    fn command<'a>(&self, config: &'a HooksConfig) -> Option<&'a str> {
+        let command = match self {
+            HookEvent::AcConnected => &config.on_ac_connected,
+            HookEvent::AcDisconnected => &config.on_ac_disconnected,
+            HookEvent::ChargingStarted => &config.on_charging_started,
+            HookEvent::ChargingStopped => &config.on_charging_stopped,
+            HookEvent::ChargeBelow { .. } => &config.on_charge_below,
+            HookEvent::PrivacyCameraChanged { .. } => &config.on_privacy_camera_changed,
+            HookEvent::PrivacyMicrophoneChanged { .. } => &config.on_privacy_microphone_changed,
+            HookEvent::PdPortAttached { .. } => &config.on_pd_port_attached,
+            HookEvent::PdPortDetached { .. } => &config.on_pd_port_detached,
+        };
+
+        command.as_deref()
+    }
+
+    /// Environment passed to the hook: the event specific details followed by
+    /// a summary of the current state
+    pub fn env(&self, info: &FrameworkInfo) -> Vec<(String, String)> {
+        let mut env = vec![("FRAMEWORK_EVENT".to_string(), self.name().to_string())];
+
+        match self {
+            HookEvent::ChargeBelow { threshold } => {
+                env.push(("FRAMEWORK_THRESHOLD".to_string(), threshold.to_string()));
+            }
+            HookEvent::PrivacyCameraChanged { enabled } => {
+                env.push(("FRAMEWORK_CAMERA_ENABLED".to_string(), on_off(*enabled)));
+            }
+            HookEvent::PrivacyMicrophoneChanged { enabled } => {
+                env.push(("FRAMEWORK_MICROPHONE_ENABLED".to_string(), on_off(*enabled)));
+            }
+            HookEvent::PdPortAttached { port, info } => {
+                env.push(("FRAMEWORK_PD_PORT".to_string(), port.to_string()));
+                env.push(("FRAMEWORK_PD_PORT_ROLE".to_string(), info.role.clone()));
+                env.push((
+                    "FRAMEWORK_PD_PORT_CHARGING_TYPE".to_string(),
+                    info.charging_type.clone(),
+                ));
+                env.push((
+                    "FRAMEWORK_PD_PORT_MAX_POWER".to_string(),
+                    info.max_power.to_string(),
+                ));
+            }
+            HookEvent::PdPortDetached { port } => {
+                env.push(("FRAMEWORK_PD_PORT".to_string(), port.to_string()));
+            }
+            _ => {}
+        }
+
+        env.push((
+            "FRAMEWORK_CHARGE_PERCENTAGE".to_string(),
+            info.charge_percentage
+                .map(|charge| charge.to_string())
+                .unwrap_or_default(),
+        ));
+        env.push((
+            "FRAMEWORK_CHARGING_STATUS".to_string(),
+            info.charging_status.clone(),
+        ));
+        env.push((
+            "FRAMEWORK_AC_CONNECTED".to_string(),
+            on_off(info.is_ac_connected),
+        ));
+
+        env
+    }
+}
+
+/// Compare two successive snapshots and list the events that happened in between
+pub fn detect(
+    config: &HooksConfig,
+    previous: &FrameworkInfo,
+    current: &FrameworkInfo,
+) -> Vec<HookEvent> {
+    let mut events = Vec::new();
+
+    match (previous.is_ac_connected, current.is_ac_connected) {
+        (false, true) => events.push(HookEvent::AcConnected),
+        (true, false) => events.push(HookEvent::AcDisconnected),
+        _ => {}
+    }
+
+    match (previous.is_charging, current.is_charging) {
+        (false, true) => events.push(HookEvent::ChargingStarted),
+        (true, false) => events.push(HookEvent::ChargingStopped),
+        _ => {}
+    }
+
+    let threshold = config.charge_below_percentage;
+    if let (Some(previous_charge), Some(current_charge)) =
+        (previous.charge_percentage, current.charge_percentage)
+    {
+        if previous_charge >= threshold && current_charge < threshold {
+            events.push(HookEvent::ChargeBelow { threshold });
+        }
+    }
+
+    if previous.is_camera_enabled != current.is_camera_enabled {
+        events.push(HookEvent::PrivacyCameraChanged {
+            enabled: current.is_camera_enabled,
+        });
+    }
+
+    if previous.is_microphone_enabled != current.is_microphone_enabled {
+        events.push(HookEvent::PrivacyMicrophoneChanged {
+            enabled: current.is_microphone_enabled,
+        });
+    }
+
+    let ports = [
+        (
+            "left_back",
+            &previous.pd_ports.left_back,
+            &current.pd_ports.left_back,
+        ),
+        (
+            "left_front",
+            &previous.pd_ports.left_front,
+            &current.pd_ports.left_front,
+        ),
+        (
+            "right_back",
+            &previous.pd_ports.right_back,
+            &current.pd_ports.right_back,
+        ),
+        (
+            "right_front",
+            &previous.pd_ports.right_front,
+            &current.pd_ports.right_front,
+        ),
+    ];
+
+    for (port, previous_port, current_port) in ports {
+        match (is_attached(previous_port), is_attached(current_port)) {
+            (false, true) => events.push(HookEvent::PdPortAttached {
+                port,
+                info: current_port.clone().unwrap_or_default(),
+            }),
+            (true, false) => events.push(HookEvent::PdPortDetached { port }),
+            _ => {}
+        }
+    }
+
+    events
+}
+
+/// Start the configured command for `event` in the background, if there is one
+pub fn run(config: &HooksConfig, event: &HookEvent, info: &FrameworkInfo) -> std::io::Result<()> {
+    let Some(command) = event.command(config) else {
+        return Ok(());
+    };
+
+    // NOTE: the hook must not write into the TUI, tokio reaps it once it exits
+    shell(command)
+        .envs(event.env(info))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> tokio::process::Command {
+    let mut shell = tokio::process::Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> tokio::process::Command {
+    let mut shell = tokio::process::Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+fn is_attached(port: &Option<PdPortInfo>) -> bool {
+    port.as_ref()
+        .is_some_and(|port| port.role != "Disconnected")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        framework::info::{FrameworkInfo, PdPortInfo},
+        hooks::{detect, HookEvent, HooksConfig},
+    };
+
+    #[test]
+    fn detect_ac_and_charge_transitions() {
+        let config = HooksConfig::default();
+        let previous = FrameworkInfo {
+            charge_percentage: Some(20),
+            is_ac_connected: true,
+            is_charging: true,
+            ..FrameworkInfo::default()
+        };
+        let current = FrameworkInfo {
+            charge_percentage: Some(19),
+            ..FrameworkInfo::default()
+        };
+
+        assert_eq!(
+            detect(&config, &previous, &current),
+            vec![
+                HookEvent::AcDisconnected,
+                HookEvent::ChargingStopped,
+                HookEvent::ChargeBelow { threshold: 20 },
+            ]
+        );
+
+        // Staying below the threshold doesn't fire again
+        assert!(detect(&config, &current, &current).is_empty());
+    }
+
+    #[test]
+    fn detect_privacy_and_pd_port_changes() {
+        let config = HooksConfig::default();
+        let previous = FrameworkInfo::default();
+        let mut current = FrameworkInfo {
+            is_microphone_enabled: true,
+            ..FrameworkInfo::default()
+        };
+        current.pd_ports.right_back = Some(PdPortInfo {
+            role: "Sink".to_string(),
+            charging_type: "PD".to_string(),
+            max_power: 60,
+            ..PdPortInfo::default()
+        });
+
+        let events = detect(&config, &previous, &current);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0],
+            HookEvent::PrivacyMicrophoneChanged { enabled: true }
+        );
+        assert_eq!(events[1].name(), "on_pd_port_attached");
+
+        let env = events[1].env(&current);
+        assert!(env.contains(&("FRAMEWORK_PD_PORT".to_string(), "right_back".to_string())));
+        assert!(env.contains(&("FRAMEWORK_PD_PORT_MAX_POWER".to_string(), "60".to_string())));
+    }
+}
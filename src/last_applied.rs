@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+
+use crate::framework::{fingerprint::Fingerprint, info::FrameworkInfo, Hardware};
+
+/// Values last chosen by the user, re-applied on startup when
+/// `reapply_on_start` is set because the EC forgets some of them across
+/// power cycles and BIOS updates
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LastApplied {
+    pub max_charge_limit: Option<u8>,
+    pub min_charge_limit: Option<u8>,
+    pub keyboard_brightness: Option<u8>,
+    pub fingerprint_brightness: Option<u8>,
+}
+
+/// Value that differed from [`LastApplied`] and was written again
+#[derive(Debug, Clone, PartialEq)]
+pub struct Correction {
+    pub setting: &'static str,
+    pub found: Option<u8>,
+    pub applied: u8,
+}
+
+impl std::fmt::Display for Correction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.found {
+            Some(found) => write!(f, "{} {}% -> {}%", self.setting, found, self.applied),
+            None => write!(f, "{} N/A -> {}%", self.setting, self.applied),
+        }
+    }
+}
+
+impl LastApplied {
+    pub fn is_empty(&self) -> bool {
+        *self == LastApplied::default()
+    }
+
+    /// Values that don't match the snapshot, the fingerprint brightness is
+    /// compared with the closest one the LED supports
+    pub fn corrections(&self, info: &FrameworkInfo, fingerprint: &Fingerprint) -> Vec<Correction> {
+        let settings = [
+            (
+                "max charge limit",
+                self.max_charge_limit,
+                info.max_charge_limit,
+            ),
+            (
+                "min charge limit",
+                self.min_charge_limit,
+                info.min_charge_limit,
+            ),
+            (
+                "keyboard brightness",
+                self.keyboard_brightness,
+                info.kb_brightness_percentage,
+            ),
+            (
+                "fingerprint brightness",
+                self.fingerprint_brightness
+                    .map(|percentage| fingerprint.validate_led_brightness(percentage)),
+                info.fp_brightness_percentage,
+            ),
+        ];
+
+        settings
+            .into_iter()
+            .filter_map(|(setting, expected, found)| {
+                let applied = expected?;
+
+                (found != Some(applied)).then_some(Correction {
+                    setting,
+                    found,
+                    applied,
+                })
+            })
+            .collect()
+    }
+
+    /// Write back every value that differs from the hardware and report them
+    pub fn reapply(
+        &self,
+        hardware: &mut dyn Hardware,
+        fingerprint: &Fingerprint,
+    ) -> color_eyre::Result<Vec<Correction>> {
+        let info = hardware.get_info()?;
+        let corrections = self.corrections(&info, fingerprint);
+
+        let charge_limits_differ = corrections
+            .iter()
+            .any(|correction| correction.setting.ends_with("charge limit"));
+        if charge_limits_differ {
+            let min = self.min_charge_limit.or(info.min_charge_limit).unwrap_or(0);
+            let max = self
+                .max_charge_limit
+                .or(info.max_charge_limit)
+                .unwrap_or(100);
+
+            hardware.set_charge_limits(min, max)?;
+        }
+        if let Some(percentage) = self
+            .keyboard_brightness
+            .filter(|percentage| info.kb_brightness_percentage != Some(*percentage))
+        {
+            hardware.set_kb_brightness(percentage)?;
+        }
+        if let Some(percentage) = self
+            .fingerprint_brightness
+            .map(|percentage| fingerprint.validate_led_brightness(percentage))
+            .filter(|percentage| info.fp_brightness_percentage != Some(*percentage))
+        {
+            hardware.set_fp_brightness(percentage)?;
+        }
+
+        Ok(corrections)
+    }
+}
+
+/// One line summary of the corrections for logs and toasts
+pub fn report(corrections: &[Correction]) -> String {
+    if corrections.is_empty() {
+        "Last applied settings are in place".to_string()
+    } else {
+        format!(
+            "Restored {}",
+            corrections
+                .iter()
+                .map(|correction| correction.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        framework::{fingerprint::Fingerprint, info::FrameworkInfo},
+        last_applied::{report, LastApplied},
+    };
+
+    #[test]
+    fn corrections_list_only_differing_values() {
+        let last_applied = LastApplied {
+            max_charge_limit: Some(80),
+            keyboard_brightness: Some(30),
+            fingerprint_brightness: Some(50),
+            ..LastApplied::default()
+        };
+        let info = FrameworkInfo {
+            max_charge_limit: Some(100),
+            min_charge_limit: Some(0),
+            kb_brightness_percentage: Some(30),
+            ..FrameworkInfo::default()
+        };
+
+        let corrections = last_applied.corrections(&info, &Fingerprint::percentage());
+
+        assert_eq!(
+            report(&corrections),
+            "Restored max charge limit 100% -> 80%, fingerprint brightness N/A -> 50%"
+        );
+        assert!(LastApplied::default()
+            .corrections(&info, &Fingerprint::percentage())
+            .is_empty());
+    }
+
+    #[test]
+    fn fingerprint_level_readback_is_not_a_correction() {
+        let last_applied = LastApplied {
+            fingerprint_brightness: Some(50),
+            ..LastApplied::default()
+        };
+        let info = FrameworkInfo {
+            fp_brightness_percentage: Some(55),
+            ..FrameworkInfo::default()
+        };
+
+        assert!(last_applied
+            .corrections(&info, &Fingerprint::level())
+            .is_empty());
+        assert_eq!(
+            report(&last_applied.corrections(&info, &Fingerprint::percentage())),
+            "Restored fingerprint brightness 55% -> 50%"
+        );
+    }
+}
@@ -1,5 +1,16 @@
+pub mod alert;
 pub mod app;
 pub mod config;
+#[cfg(unix)]
+pub mod daemon;
 pub mod event;
 pub mod framework;
+pub mod hooks;
+pub mod last_applied;
+pub mod paths;
+pub mod profile;
+pub mod rules;
+pub mod smart_charge;
+pub mod state;
+pub mod status;
 pub mod tui;
@@ -1,30 +1,326 @@
-use framework_tool_tui::app::App;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::NaiveTime;
+use clap::Parser;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+#[cfg(unix)]
+use framework_tool_tui::daemon::{
+    client::Client, dbus, Daemon, DEFAULT_SOCKET_GROUP, DEFAULT_SOCKET_PATH,
+};
+use framework_tool_tui::{
+    app::App,
+    config::{
+        layer::{self, Machine},
+        Config, ConfigError, CONFIG_VERSION,
+    },
+    framework::{fingerprint::Fingerprint, Framework, Hardware},
+    profile,
+    state::State,
+    status::{self, StatusOutput},
+    tui::theme::{auto, ThemeId},
+};
 #[cfg(unix)]
 use uzers::get_current_uid;
 
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Connect to a running daemon instead of accessing the EC directly
+    #[cfg(unix)]
+    #[arg(long)]
+    connect: bool,
+
+    /// Path of the daemon socket
+    #[cfg(unix)]
+    #[arg(long, global = true, default_value = DEFAULT_SOCKET_PATH)]
+    socket: PathBuf,
+
+    /// Print a status line built from a template instead of starting the TUI,
+    /// e.g. "{charge}% {status} {power}W {fan1}rpm"
+    #[arg(long, value_name = "TEMPLATE")]
+    format: Option<String>,
+
+    /// Print status lines in waybar's JSON format, --format sets the text
+    #[arg(long)]
+    waybar: bool,
+
+    /// Print a single status line and exit
+    #[arg(long)]
+    once: bool,
+
+    /// Apply a profile from the config before starting, e.g. --profile travel
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Hold the normal charge limit and reach 100% by the next HH:MM, e.g. 08:00
+    #[arg(long, value_name = "HH:MM")]
+    full_by: Option<NaiveTime>,
+
+    /// Use this config file instead of the default one
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Theme for this session, e.g. catppuccin_mocha or the name of a custom theme
+    #[arg(long, value_name = "NAME", value_parser = ThemeId::from_str)]
+    theme: Option<ThemeId>,
+
+    /// Refresh interval for this session in milliseconds
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(100..=5000))]
+    tick_interval: Option<u64>,
+
+    /// Don't write any change to the config file
+    #[arg(long)]
+    no_save: bool,
+
+    /// Only monitor, never change hardware settings. Implies --no-save
+    #[arg(long)]
+    read_only: bool,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Run the privileged daemon that owns the EC and serves TUI clients
+    #[cfg(unix)]
+    Daemon {
+        /// Group whose members are allowed to connect
+        #[arg(long, default_value = DEFAULT_SOCKET_GROUP)]
+        group: String,
+
+        /// Also expose the hardware state as a D-Bus service on the given bus
+        #[arg(long, value_enum)]
+        dbus: Option<dbus::Bus>,
+    },
+
+    /// Inspect the config file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum ConfigCommand {
+    /// Validate the config file and print the effective config, nothing is written
+    Check {
+        /// File to check instead of --config or the default one
+        path: Option<PathBuf>,
+
+        /// Print every value with the file and section it comes from
+        #[arg(long)]
+        sources: bool,
+    },
+}
+
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
 
-    check_permissions()?;
+    let cli = Cli::parse();
+
+    if let Some(Command::Config {
+        command: ConfigCommand::Check { path, sources },
+    }) = &cli.command
+    {
+        return check_config(path.as_deref().or(cli.config.as_deref()), *sources);
+    }
+
+    #[cfg(unix)]
+    if let Some(Command::Daemon { group, dbus }) = &cli.command {
+        check_permissions()?;
+
+        let mut daemon = Daemon::new()?;
+        let (config, config_errors) =
+            Config::load_or_create_from(cli.config.as_deref(), &daemon.machine())?;
+        for error in config_errors {
+            eprintln!("{}", error);
+        }
+
+        return daemon.run(&cli.socket, group, *dbus, config).await;
+    }
 
+    let (mut hardware, fingerprint) = connect_hardware(&cli)?;
+    let (config, config_errors) = load_config(&cli, &Machine::from_info(&hardware.get_info()?))?;
+
+    if let Some(name) = &cli.profile {
+        if cli.read_only {
+            return Err(color_eyre::Report::msg(
+                "--profile changes hardware settings and can't be used with --read-only.",
+            ));
+        }
+
+        profile::activate(
+            name,
+            &config.profiles,
+            hardware.as_mut(),
+            &fingerprint,
+            &mut State::load(),
+        )?;
+    }
+
+    if cli.waybar || cli.format.is_some() || cli.once {
+        let output = if cli.waybar {
+            StatusOutput::Waybar(
+                cli.format
+                    .unwrap_or_else(|| status::DEFAULT_WAYBAR_TEMPLATE.to_string()),
+            )
+        } else {
+            StatusOutput::Template(
+                cli.format
+                    .unwrap_or_else(|| status::DEFAULT_TEMPLATE.to_string()),
+            )
+        };
+        let interval = Duration::from_millis(config.tick_interval_ms);
+        for error in config_errors {
+            eprintln!("{}", error);
+        }
+
+        return status::run(hardware, output, cli.once, interval).await;
+    }
+
+    let mouse = config.mouse;
+    let mut app = App::new(hardware, fingerprint, config, cli.read_only)?;
+    if !config_errors.is_empty() {
+        app.set_error(
+            config_errors
+                .iter()
+                .map(ConfigError::to_string)
+                .collect::<Vec<String>>()
+                .join("; "),
+        );
+    }
+    if let Some(full_by) = cli.full_by {
+        app.schedule_full_charge(full_by)?;
+    }
     let mut terminal = ratatui::init();
-    let mut app = App::new()?;
+    if mouse {
+        crossterm::execute!(std::io::stdout(), EnableMouseCapture)?;
+    }
 
     let result = app.run(&mut terminal).await;
 
+    if mouse {
+        let _ = crossterm::execute!(std::io::stdout(), DisableMouseCapture);
+    }
     ratatui::restore();
 
     result
 }
 
+fn check_config(path: Option<&Path>, sources: bool) -> color_eyre::Result<()> {
+    let (config, migrated_from) = Config::check(path, &detect_machine())?
+        .map_err(|error| color_eyre::Report::msg(error.to_string()))?;
+    config.keys.check().map_err(color_eyre::Report::msg)?;
+
+    if let Some(version) = migrated_from {
+        eprintln!(
+            "Config version {} will be upgraded to {} on the next start",
+            version, CONFIG_VERSION
+        );
+    }
+    if sources {
+        for (key, value) in layer::flatten(&toml::Table::try_from(&config)?) {
+            println!("{} = {}  # {}", key, value, config.source(&key));
+        }
+    } else {
+        print!("{}", toml::to_string_pretty(&config)?);
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn detect_machine() -> Machine {
+    // NOTE: the SMBIOS tables are only readable by root
+    if get_current_uid() == 0 {
+        Machine::detect()
+    } else {
+        eprintln!("Not running as root, machine sections are left out");
+        Machine::default()
+    }
+}
+
+#[cfg(windows)]
+fn detect_machine() -> Machine {
+    Machine::detect()
+}
+
+/// Load the config file and apply the overrides given on the command line,
+/// the overrides only last for this session
+fn load_config(cli: &Cli, machine: &Machine) -> color_eyre::Result<(Config, Vec<ConfigError>)> {
+    let (mut config, config_errors) = Config::load_or_create_from(cli.config.as_deref(), machine)?;
+    let mut overrides = toml::Table::new();
+
+    // NOTE: only the TUI queries the terminal, status lines may not even run in one
+    let status_mode = cli.waybar || cli.format.is_some() || cli.once;
+    if cli.theme.is_none() && config.auto_theme.enabled && !status_mode {
+        if let Some(background) = auto::detect_background(auto::QUERY_TIMEOUT) {
+            let theme = config.auto_theme.pick(background);
+            let mut detected = toml::Table::new();
+
+            detected.insert("theme".to_string(), toml::Value::try_from(theme)?);
+            config.set_overrides("auto theme", detected)?;
+        }
+    }
+
+    if let Some(theme) = cli.theme {
+        overrides.insert("theme".to_string(), toml::Value::try_from(theme)?);
+    }
+    if let Some(tick_interval_ms) = cli.tick_interval {
+        overrides.insert(
+            "tick_interval_ms".to_string(),
+            toml::Value::try_from(tick_interval_ms)?,
+        );
+    }
+    config.set_overrides("command line", overrides)?;
+    config.set_no_save(cli.no_save || cli.read_only);
+
+    Ok((config, config_errors))
+}
+
+#[cfg(unix)]
+fn connect_hardware(cli: &Cli) -> color_eyre::Result<(Box<dyn Hardware>, Arc<Fingerprint>)> {
+    // NOTE: unprivileged users go through the daemon when it's available
+    let use_daemon = cli.connect || (get_current_uid() != 0 && cli.socket.exists());
+
+    if use_daemon {
+        let client = Client::connect(&cli.socket)?;
+        let fingerprint = Arc::new(client.fingerprint());
+
+        Ok((Box::new(client), fingerprint))
+    } else {
+        open_framework()
+    }
+}
+
+#[cfg(windows)]
+fn connect_hardware(_cli: &Cli) -> color_eyre::Result<(Box<dyn Hardware>, Arc<Fingerprint>)> {
+    open_framework()
+}
+
+fn open_framework() -> color_eyre::Result<(Box<dyn Hardware>, Arc<Fingerprint>)> {
+    check_permissions()?;
+
+    let framework = Framework::open()?;
+    let fingerprint = framework.fingerprint();
+
+    Ok((Box::new(framework), fingerprint))
+}
+
 #[cfg(unix)]
 fn check_permissions() -> color_eyre::Result<()> {
     let is_admin = get_current_uid() == 0;
 
     if !is_admin {
         return Err(color_eyre::Report::msg(
-            "The application needs to be run with root privileges.",
+            "The application needs to be run with root privileges, or connect to a running daemon with --connect.",
         ));
     }
 
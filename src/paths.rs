@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use uzers::os::unix::UserExt;
+
+const APP_DIR: &str = "framework-tool-tui";
+
+/// Directory of `config.toml`, `$XDG_CONFIG_HOME/framework-tool-tui` on Linux
+pub fn config_dir() -> color_eyre::Result<PathBuf> {
+    let dir = match sudo_home() {
+        Some(home) => home.join(".config"),
+        None => dirs::config_dir()
+            .ok_or_else(|| color_eyre::eyre::eyre!("Could not determine config directory"))?,
+    };
+
+    create_dir(dir.join(APP_DIR))
+}
+
+/// Directory of the runtime state, `$XDG_DATA_HOME/framework-tool-tui` on Linux
+pub fn data_dir() -> color_eyre::Result<PathBuf> {
+    let dir = match sudo_home() {
+        Some(home) => home.join(".local").join("share"),
+        None => dirs::data_local_dir()
+            .ok_or_else(|| color_eyre::eyre::eyre!("Could not determine local data directory"))?,
+    };
+
+    create_dir(dir.join(APP_DIR))
+}
+
+/// Places `config.toml` used to be stored in, most likely first
+pub fn legacy_config_paths() -> Vec<PathBuf> {
+    sudo_home()
+        .map(|home| home.join(".local").join("share"))
+        .into_iter()
+        .chain(dirs::data_local_dir())
+        .map(|dir| dir.join(APP_DIR).join("config.toml"))
+        .collect()
+}
+
+fn create_dir(dir: PathBuf) -> color_eyre::Result<PathBuf> {
+    if !dir.exists() {
+        fs::create_dir_all(&dir)?;
+        hand_over(&dir)?;
+    }
+
+    Ok(dir)
+}
+
+/// Home of the user who started the app through sudo, so that running as
+/// root doesn't hide the files in `/root`
+#[cfg(unix)]
+fn sudo_home() -> Option<PathBuf> {
+    if uzers::get_current_uid() != 0 {
+        return None;
+    }
+
+    let user = uzers::get_user_by_name(&std::env::var_os("SUDO_USER")?)?;
+
+    Some(user.home_dir().to_path_buf())
+}
+
+#[cfg(windows)]
+fn sudo_home() -> Option<PathBuf> {
+    None
+}
+
+/// Give a file created under sudo to the invoking user, so that they can
+/// still edit it without root
+#[cfg(unix)]
+pub fn hand_over(path: &Path) -> color_eyre::Result<()> {
+    let id = |name: &str| std::env::var(name).ok()?.parse::<u32>().ok();
+
+    if let (Some(_), Some(uid), Some(gid)) = (sudo_home(), id("SUDO_UID"), id("SUDO_GID")) {
+        std::os::unix::fs::chown(path, Some(uid), Some(gid))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn hand_over(_path: &Path) -> color_eyre::Result<()> {
+    Ok(())
+}
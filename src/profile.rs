@@ -0,0 +1,309 @@
+use std::collections::BTreeMap;
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    framework::{fingerprint::Fingerprint, info::FrameworkInfo, Hardware, MAX_CHARGE_LIMIT_MIN},
+    state::State,
+};
+
+/// Bundle of settings applied at once, unset fields are left alone
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub max_charge_limit: Option<u8>,
+    pub min_charge_limit: Option<u8>,
+    pub keyboard_brightness: Option<u8>,
+    pub fingerprint_brightness: Option<u8>,
+    pub fan: Option<FanMode>,
+}
+
+/// `fan = "auto"` or `fan = { duty = 40 }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FanMode {
+    Auto,
+    Duty(u8),
+}
+
+pub fn default_profiles() -> BTreeMap<String, Profile> {
+    BTreeMap::from([
+        (
+            "desk".to_string(),
+            Profile {
+                max_charge_limit: Some(80),
+                keyboard_brightness: Some(50),
+                fan: Some(FanMode::Auto),
+                ..Profile::default()
+            },
+        ),
+        (
+            "presentation".to_string(),
+            Profile {
+                keyboard_brightness: Some(0),
+                // NOTE: the sensor LED can't go below 5%
+                fingerprint_brightness: Some(5),
+                ..Profile::default()
+            },
+        ),
+        (
+            "storage".to_string(),
+            Profile {
+                max_charge_limit: Some(60),
+                min_charge_limit: Some(50),
+                keyboard_brightness: Some(0),
+                ..Profile::default()
+            },
+        ),
+        (
+            "travel".to_string(),
+            Profile {
+                max_charge_limit: Some(100),
+                keyboard_brightness: Some(30),
+                fan: Some(FanMode::Auto),
+                ..Profile::default()
+            },
+        ),
+    ])
+}
+
+/// Deserialize the `profiles` table, profiles that can't be applied are rejected
+pub fn deserialize_profiles<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, Profile>, D::Error> {
+    let profiles = BTreeMap::<String, Profile>::deserialize(deserializer)?;
+
+    for (name, profile) in &profiles {
+        profile
+            .validate()
+            .map_err(|error| D::Error::custom(format!("profile \"{}\": {}", name, error)))?;
+    }
+
+    Ok(profiles)
+}
+
+/// Apply the profile called `name` and remember it as the active one
+pub fn activate(
+    name: &str,
+    profiles: &BTreeMap<String, Profile>,
+    hardware: &mut dyn Hardware,
+    fingerprint: &Fingerprint,
+    state: &mut State,
+) -> color_eyre::Result<()> {
+    let profile = profiles.get(name).ok_or_else(|| {
+        color_eyre::eyre::eyre!(
+            "Unknown profile \"{}\", available profiles: {}",
+            name,
+            profiles.keys().cloned().collect::<Vec<String>>().join(", ")
+        )
+    })?;
+    let info = hardware.get_info()?;
+
+    profile.apply(hardware, fingerprint, &info)?;
+    state.set_active_profile(Some(name.to_string()))
+}
+
+impl Profile {
+    /// Why the profile can't be applied, e.g. a percentage over 100
+    pub fn validate(&self) -> Result<(), String> {
+        let fan_duty = match self.fan {
+            Some(FanMode::Duty(percentage)) => Some(percentage),
+            _ => None,
+        };
+        let percentages = [
+            ("max_charge_limit", self.max_charge_limit),
+            ("min_charge_limit", self.min_charge_limit),
+            ("keyboard_brightness", self.keyboard_brightness),
+            ("fingerprint_brightness", self.fingerprint_brightness),
+            ("fan duty", fan_duty),
+        ];
+
+        for (key, value) in percentages {
+            if let Some(value) = value.filter(|value| *value > 100) {
+                return Err(format!("{} {} is over 100", key, value));
+            }
+        }
+        if let Some(max) = self
+            .max_charge_limit
+            .filter(|max| *max < MAX_CHARGE_LIMIT_MIN)
+        {
+            return Err(format!(
+                "max_charge_limit {} is below {}",
+                max, MAX_CHARGE_LIMIT_MIN
+            ));
+        }
+        if let (Some(min), Some(max)) = (self.min_charge_limit, self.max_charge_limit) {
+            if min > max {
+                return Err(format!(
+                    "min_charge_limit {} is above max_charge_limit {}",
+                    min, max
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn apply(
+        &self,
+        hardware: &mut dyn Hardware,
+        fingerprint: &Fingerprint,
+        info: &FrameworkInfo,
+    ) -> color_eyre::Result<()> {
+        if self.min_charge_limit.is_some() || self.max_charge_limit.is_some() {
+            let min = self.min_charge_limit.or(info.min_charge_limit).unwrap_or(0);
+            let max = self
+                .max_charge_limit
+                .or(info.max_charge_limit)
+                .unwrap_or(100);
+
+            hardware.set_charge_limits(min, max)?;
+        }
+        if let Some(percentage) = self.keyboard_brightness {
+            hardware.set_kb_brightness(percentage)?;
+        }
+        if let Some(percentage) = self.fingerprint_brightness {
+            hardware.set_fp_brightness(fingerprint.validate_led_brightness(percentage))?;
+        }
+        match self.fan {
+            Some(FanMode::Auto) => hardware.set_fan_duty(None)?,
+            Some(FanMode::Duty(percentage)) => hardware.set_fan_duty(Some(percentage))?,
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    /// Settings whose live value no longer matches the profile.
+    ///
+    /// The fan mode isn't reported by the EC, so it never counts as drifted.
+    /// The fingerprint brightness is compared with the closest one the LED supports.
+    pub fn drift(&self, info: &FrameworkInfo, fingerprint: &Fingerprint) -> Vec<&'static str> {
+        let settings = [
+            (
+                "max charge limit",
+                self.max_charge_limit,
+                info.max_charge_limit,
+            ),
+            (
+                "min charge limit",
+                self.min_charge_limit,
+                info.min_charge_limit,
+            ),
+            (
+                "keyboard brightness",
+                self.keyboard_brightness,
+                info.kb_brightness_percentage,
+            ),
+            (
+                "fingerprint brightness",
+                self.fingerprint_brightness
+                    .map(|percentage| fingerprint.validate_led_brightness(percentage)),
+                info.fp_brightness_percentage,
+            ),
+        ];
+
+        settings
+            .into_iter()
+            .filter(|(_, expected, actual)| {
+                expected.is_some() && actual.is_some() && expected != actual
+            })
+            .map(|(name, _, _)| name)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        framework::{fingerprint::Fingerprint, info::FrameworkInfo},
+        profile::{default_profiles, FanMode, Profile},
+    };
+
+    #[test]
+    fn drift_ignores_unset_and_unknown_values() {
+        let profile = Profile {
+            max_charge_limit: Some(80),
+            keyboard_brightness: Some(50),
+            fingerprint_brightness: Some(20),
+            ..Profile::default()
+        };
+        let mut info = FrameworkInfo {
+            max_charge_limit: Some(80),
+            min_charge_limit: Some(10),
+            kb_brightness_percentage: Some(50),
+            ..FrameworkInfo::default()
+        };
+
+        assert!(profile.drift(&info, &Fingerprint::percentage()).is_empty());
+
+        info.kb_brightness_percentage = Some(100);
+        assert_eq!(
+            profile.drift(&info, &Fingerprint::percentage()),
+            vec!["keyboard brightness"]
+        );
+    }
+
+    #[test]
+    fn drift_compares_fingerprint_levels() {
+        let profile = Profile {
+            fingerprint_brightness: Some(20),
+            ..Profile::default()
+        };
+        let mut info = FrameworkInfo {
+            fp_brightness_percentage: Some(40),
+            ..FrameworkInfo::default()
+        };
+
+        assert!(profile.drift(&info, &Fingerprint::level()).is_empty());
+
+        info.fp_brightness_percentage = Some(55);
+        assert_eq!(
+            profile.drift(&info, &Fingerprint::level()),
+            vec!["fingerprint brightness"]
+        );
+    }
+
+    #[test]
+    fn profiles_that_cant_be_applied_are_rejected() {
+        let parse = |content: &str| {
+            toml::from_str::<crate::config::Config>(content)
+                .map_err(|error| error.message().to_string())
+        };
+
+        assert_eq!(
+            parse("[profiles.quiet]\nfan = { duty = 140 }").unwrap_err(),
+            "profile \"quiet\": fan duty 140 is over 100"
+        );
+        assert_eq!(
+            parse("[profiles.storage]\nmin_charge_limit = 80\nmax_charge_limit = 60").unwrap_err(),
+            "profile \"storage\": min_charge_limit 80 is above max_charge_limit 60"
+        );
+        assert_eq!(
+            parse("[profiles.low]\nmax_charge_limit = 10").unwrap_err(),
+            "profile \"low\": max_charge_limit 10 is below 25"
+        );
+        assert!(default_profiles()
+            .values()
+            .all(|profile| profile.validate().is_ok()));
+    }
+
+    #[test]
+    fn profiles_deserialize_from_toml() {
+        let profiles: std::collections::BTreeMap<String, Profile> = toml::from_str(
+            r#"
+            [desk]
+            max_charge_limit = 80
+            fan = "auto"
+
+            [render]
+            fan = { duty = 100 }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(profiles["desk"].fan, Some(FanMode::Auto));
+        assert_eq!(profiles["render"].fan, Some(FanMode::Duty(100)));
+        assert_eq!(default_profiles().len(), 4);
+    }
+}
@@ -0,0 +1,563 @@
+use chrono::{NaiveDateTime, NaiveTime};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    app::AppEvent,
+    framework::{fingerprint::Fingerprint, info::FrameworkInfo, MAX_CHARGE_LIMIT_MIN},
+};
+
+/// Declarative automation rule, e.g.
+///
+/// ```toml
+/// [[rules]]
+/// name = "Backlight off at night"
+/// when = { between = ["22:00", "07:00"] }
+/// then = { keyboard_brightness = 0 }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub when: Condition,
+    #[serde(default)]
+    pub then: Action,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// All the set fields have to hold for the rule to apply
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Condition {
+    pub ac_connected: Option<bool>,
+    pub charging: Option<bool>,
+    pub charge_below: Option<u32>,
+    pub charge_above: Option<u32>,
+    /// Holds only while a charger is attached and offers less than this many watts
+    pub charger_max_power_below: Option<u32>,
+    /// Local time window `[start, end)`, wraps around midnight when `start > end`
+    pub between: Option<[NaiveTime; 2]>,
+}
+
+/// Values to keep while the rule applies
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Action {
+    pub max_charge_limit: Option<u8>,
+    pub keyboard_brightness: Option<u8>,
+    pub fingerprint_brightness: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleStatus {
+    pub name: String,
+    pub enabled: bool,
+    pub last_fired: Option<NaiveDateTime>,
+}
+
+/// Deserialize the `rules` list, rules whose action can't be applied are rejected
+pub fn deserialize_rules<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Rule>, D::Error> {
+    let rules = Vec::<Rule>::deserialize(deserializer)?;
+
+    for rule in &rules {
+        rule.then
+            .validate()
+            .map_err(|error| D::Error::custom(format!("rule \"{}\": {}", rule.name, error)))?;
+    }
+
+    Ok(rules)
+}
+
+impl Action {
+    /// Why the action can't be applied, e.g. a percentage over 100
+    pub fn validate(&self) -> Result<(), String> {
+        let percentages = [
+            ("max_charge_limit", self.max_charge_limit),
+            ("keyboard_brightness", self.keyboard_brightness),
+            ("fingerprint_brightness", self.fingerprint_brightness),
+        ];
+
+        for (key, value) in percentages {
+            if let Some(value) = value.filter(|value| *value > 100) {
+                return Err(format!("{} {} is over 100", key, value));
+            }
+        }
+        match self.max_charge_limit {
+            Some(max) if max < MAX_CHARGE_LIMIT_MIN => Err(format!(
+                "max_charge_limit {} is below {}",
+                max, MAX_CHARGE_LIMIT_MIN
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Condition {
+    pub fn matches(&self, info: &FrameworkInfo, now: NaiveDateTime) -> bool {
+        let charge = info.charge_percentage;
+
+        self.ac_connected
+            .is_none_or(|ac_connected| info.is_ac_connected == ac_connected)
+            && self
+                .charging
+                .is_none_or(|charging| info.is_charging == charging)
+            && self
+                .charge_below
+                .is_none_or(|below| charge.is_some_and(|charge| charge < below))
+            && self
+                .charge_above
+                .is_none_or(|above| charge.is_some_and(|charge| charge > above))
+            && self.charger_max_power_below.is_none_or(|below| {
+                charger_max_power(info).is_some_and(|max_power| max_power < below)
+            })
+            && self
+                .between
+                .is_none_or(|[start, end]| is_within(now.time(), start, end))
+    }
+}
+
+/// Evaluates [`Rule`]s against each snapshot and emits the [`AppEvent`]s needed
+/// to bring the hardware in line with them.
+///
+/// Rules are level triggered: while a rule applies, every snapshot that
+/// deviates from its action is corrected again. When several rules apply and
+/// set the same value, the later one wins.
+pub struct RulesEngine {
+    rules: Vec<Rule>,
+    last_fired: Vec<Option<NaiveDateTime>>,
+    /// Set while something else, e.g. a smart charging plan, owns the max charge limit
+    charge_limit_held: bool,
+}
+
+impl RulesEngine {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        let last_fired = vec![None; rules.len()];
+
+        Self {
+            rules,
+            last_fired,
+            charge_limit_held: false,
+        }
+    }
+
+    /// Replace the rules, e.g. after a config reload. Rules that kept their
+    /// place and content keep their last fired time.
+    pub fn set_rules(&mut self, rules: Vec<Rule>) {
+        self.last_fired = rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                (self.rules.get(index) == Some(rule))
+                    .then(|| self.last_fired[index])
+                    .flatten()
+            })
+            .collect();
+        self.rules = rules;
+    }
+
+    pub fn hold_charge_limit(&mut self, held: bool) {
+        self.charge_limit_held = held;
+    }
+
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        if let Some(rule) = self.rules.get_mut(index) {
+            rule.enabled = enabled;
+        }
+    }
+
+    pub fn statuses(&self) -> Vec<RuleStatus> {
+        self.rules
+            .iter()
+            .zip(&self.last_fired)
+            .map(|(rule, last_fired)| RuleStatus {
+                name: rule.name.clone(),
+                enabled: rule.enabled,
+                last_fired: *last_fired,
+            })
+            .collect()
+    }
+
+    /// Events needed to apply the rules, each with the index of the rule that asked for it
+    pub fn evaluate(
+        &mut self,
+        info: &FrameworkInfo,
+        fingerprint: &Fingerprint,
+        now: NaiveDateTime,
+    ) -> Vec<(usize, AppEvent)> {
+        // Desired value and the index of the rule that asked for it
+        let mut max_charge_limit = None;
+        let mut keyboard_brightness = None;
+        let mut fingerprint_brightness = None;
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            if !rule.enabled || !rule.when.matches(info, now) {
+                continue;
+            }
+
+            if let Some(value) = rule
+                .then
+                .max_charge_limit
+                .filter(|_| !self.charge_limit_held)
+            {
+                max_charge_limit = Some((value, index));
+            }
+            if let Some(value) = rule.then.keyboard_brightness {
+                keyboard_brightness = Some((value, index));
+            }
+            if let Some(value) = rule.then.fingerprint_brightness {
+                fingerprint_brightness = Some((value, index));
+            }
+        }
+
+        let mut events = Vec::new();
+
+        if let Some((value, index)) = max_charge_limit {
+            if info.max_charge_limit != Some(value) {
+                events.push((index, AppEvent::SetMaxChargeLimit(value)));
+                self.last_fired[index] = Some(now);
+            }
+        }
+        if let Some((value, index)) = keyboard_brightness {
+            if info.kb_brightness_percentage != Some(value) {
+                events.push((index, AppEvent::SetKeyboardBrightness(value)));
+                self.last_fired[index] = Some(now);
+            }
+        }
+        if let Some((value, index)) = fingerprint_brightness {
+            // NOTE: a level only EC reads back the level's percentage, not the one asked for
+            let value = fingerprint.validate_led_brightness(value);
+
+            if info.fp_brightness_percentage != Some(value) {
+                events.push((index, AppEvent::SetFingerprintBrightness(value)));
+                self.last_fired[index] = Some(now);
+            }
+        }
+
+        events
+    }
+}
+
+/// Highest power offered by an attached charger, in watts
+fn charger_max_power(info: &FrameworkInfo) -> Option<u32> {
+    let ports = &info.pd_ports;
+
+    [
+        &ports.left_back,
+        &ports.left_front,
+        &ports.right_back,
+        &ports.right_front,
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|port| port.role.starts_with("Sink"))
+    .map(|port| port.max_power)
+    .max()
+}
+
+fn is_within(time: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        start <= time && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+    use crate::{
+        app::AppEvent,
+        framework::{
+            fingerprint::Fingerprint,
+            info::{FrameworkInfo, PdPortInfo},
+        },
+        rules::{Action, Condition, Rule, RulesEngine},
+    };
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn rule(name: &str, when: Condition, then: Action) -> Rule {
+        Rule {
+            name: name.to_string(),
+            enabled: true,
+            when,
+            then,
+        }
+    }
+
+    #[test]
+    fn rule_fires_until_the_value_is_applied() {
+        let mut engine = RulesEngine::new(vec![rule(
+            "Backlight off on battery",
+            Condition {
+                ac_connected: Some(false),
+                ..Condition::default()
+            },
+            Action {
+                keyboard_brightness: Some(0),
+                ..Action::default()
+            },
+        )]);
+        let mut info = FrameworkInfo {
+            kb_brightness_percentage: Some(50),
+            ..FrameworkInfo::default()
+        };
+
+        assert_eq!(
+            engine.evaluate(&info, &Fingerprint::percentage(), at(12, 0)),
+            vec![(0, AppEvent::SetKeyboardBrightness(0))]
+        );
+        assert_eq!(engine.statuses()[0].last_fired, Some(at(12, 0)));
+
+        info.kb_brightness_percentage = Some(0);
+        assert!(engine
+            .evaluate(&info, &Fingerprint::percentage(), at(12, 1))
+            .is_empty());
+        assert_eq!(engine.statuses()[0].last_fired, Some(at(12, 0)));
+
+        info.is_ac_connected = true;
+        info.kb_brightness_percentage = Some(50);
+        assert!(engine
+            .evaluate(&info, &Fingerprint::percentage(), at(12, 2))
+            .is_empty());
+    }
+
+    #[test]
+    fn time_window_wraps_around_midnight() {
+        let night = |time: &str| {
+            let mut engine = RulesEngine::new(vec![rule(
+                "Night",
+                Condition {
+                    between: Some([
+                        NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+                        NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+                    ]),
+                    ..Condition::default()
+                },
+                Action {
+                    keyboard_brightness: Some(0),
+                    ..Action::default()
+                },
+            )]);
+            let info = FrameworkInfo {
+                kb_brightness_percentage: Some(30),
+                ..FrameworkInfo::default()
+            };
+            let (hour, minute) = time.split_once(':').unwrap();
+
+            !engine
+                .evaluate(
+                    &info,
+                    &Fingerprint::percentage(),
+                    at(hour.parse().unwrap(), minute.parse().unwrap()),
+                )
+                .is_empty()
+        };
+
+        assert!(night("23:30"));
+        assert!(night("06:59"));
+        assert!(!night("07:00"));
+        assert!(!night("12:00"));
+    }
+
+    #[test]
+    fn later_rule_wins_and_disabled_rules_are_skipped() {
+        let weak_charger = Condition {
+            charger_max_power_below: Some(60),
+            ..Condition::default()
+        };
+        let mut engine = RulesEngine::new(vec![
+            rule(
+                "Default limit",
+                Condition::default(),
+                Action {
+                    max_charge_limit: Some(80),
+                    ..Action::default()
+                },
+            ),
+            rule(
+                "Weak charger",
+                weak_charger,
+                Action {
+                    max_charge_limit: Some(60),
+                    ..Action::default()
+                },
+            ),
+        ]);
+        let mut info = FrameworkInfo {
+            max_charge_limit: Some(100),
+            ..FrameworkInfo::default()
+        };
+        info.pd_ports.left_back = Some(PdPortInfo {
+            role: "Sink".to_string(),
+            max_power: 45,
+            ..PdPortInfo::default()
+        });
+
+        assert_eq!(
+            engine.evaluate(&info, &Fingerprint::percentage(), at(9, 0)),
+            vec![(1, AppEvent::SetMaxChargeLimit(60))]
+        );
+        assert_eq!(engine.statuses()[0].last_fired, None);
+
+        engine.set_enabled(1, false);
+        assert_eq!(
+            engine.evaluate(&info, &Fingerprint::percentage(), at(9, 1)),
+            vec![(0, AppEvent::SetMaxChargeLimit(80))]
+        );
+    }
+
+    #[test]
+    fn unchanged_rules_keep_their_last_fired_time() {
+        let backlight_off = rule(
+            "Backlight off",
+            Condition::default(),
+            Action {
+                keyboard_brightness: Some(0),
+                ..Action::default()
+            },
+        );
+        let limit = rule(
+            "Limit",
+            Condition::default(),
+            Action {
+                max_charge_limit: Some(80),
+                ..Action::default()
+            },
+        );
+        let mut engine = RulesEngine::new(vec![backlight_off.clone(), limit.clone()]);
+        let info = FrameworkInfo {
+            kb_brightness_percentage: Some(50),
+            max_charge_limit: Some(100),
+            ..FrameworkInfo::default()
+        };
+        engine.evaluate(&info, &Fingerprint::percentage(), at(9, 0));
+
+        let mut edited = limit;
+        edited.then.max_charge_limit = Some(60);
+        engine.set_rules(vec![backlight_off, edited]);
+
+        let last_fired = engine
+            .statuses()
+            .into_iter()
+            .map(|status| status.last_fired)
+            .collect::<Vec<_>>();
+        assert_eq!(last_fired, vec![Some(at(9, 0)), None]);
+    }
+
+    #[test]
+    fn held_charge_limit_is_left_alone() {
+        let mut engine = RulesEngine::new(vec![rule(
+            "Default limit",
+            Condition::default(),
+            Action {
+                max_charge_limit: Some(80),
+                keyboard_brightness: Some(0),
+                ..Action::default()
+            },
+        )]);
+        let info = FrameworkInfo {
+            max_charge_limit: Some(100),
+            kb_brightness_percentage: Some(50),
+            ..FrameworkInfo::default()
+        };
+
+        engine.hold_charge_limit(true);
+        assert_eq!(
+            engine.evaluate(&info, &Fingerprint::percentage(), at(9, 0)),
+            vec![(0, AppEvent::SetKeyboardBrightness(0))]
+        );
+
+        engine.hold_charge_limit(false);
+        assert_eq!(
+            engine.evaluate(&info, &Fingerprint::percentage(), at(9, 1)),
+            vec![
+                (0, AppEvent::SetMaxChargeLimit(80)),
+                (0, AppEvent::SetKeyboardBrightness(0))
+            ]
+        );
+    }
+
+    #[test]
+    fn fingerprint_rule_settles_on_the_supported_brightness() {
+        let mut engine = RulesEngine::new(vec![rule(
+            "Dim sensor",
+            Condition::default(),
+            Action {
+                fingerprint_brightness: Some(50),
+                ..Action::default()
+            },
+        )]);
+        let mut info = FrameworkInfo {
+            fp_brightness_percentage: Some(15),
+            ..FrameworkInfo::default()
+        };
+
+        assert_eq!(
+            engine.evaluate(&info, &Fingerprint::level(), at(9, 0)),
+            vec![(0, AppEvent::SetFingerprintBrightness(55))]
+        );
+
+        info.fp_brightness_percentage = Some(55);
+        assert!(engine
+            .evaluate(&info, &Fingerprint::level(), at(9, 1))
+            .is_empty());
+    }
+
+    #[test]
+    fn rules_that_cant_be_applied_are_rejected() {
+        let parse = |content: &str| {
+            toml::from_str::<crate::config::Config>(content)
+                .map_err(|error| error.message().to_string())
+        };
+
+        assert_eq!(
+            parse("[[rules]]\nname = \"Low\"\nthen = { max_charge_limit = 10 }").unwrap_err(),
+            "rule \"Low\": max_charge_limit 10 is below 25"
+        );
+        assert_eq!(
+            parse("[[rules]]\nname = \"Bright\"\nthen = { keyboard_brightness = 150 }")
+                .unwrap_err(),
+            "rule \"Bright\": keyboard_brightness 150 is over 100"
+        );
+    }
+
+    #[test]
+    fn rules_deserialize_from_toml() {
+        let rules: Vec<Rule> = toml::from_str::<toml::Table>(
+            r#"
+            [[rules]]
+            name = "Night"
+            enabled = false
+            when = { between = ["22:00", "07:00"] }
+            then = { keyboard_brightness = 0 }
+            "#,
+        )
+        .unwrap()["rules"]
+            .clone()
+            .try_into()
+            .unwrap();
+
+        assert_eq!(rules.len(), 1);
+        assert!(!rules[0].enabled);
+        assert_eq!(
+            rules[0].when.between,
+            Some([
+                NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+                NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            ])
+        );
+    }
+}
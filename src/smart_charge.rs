@@ -0,0 +1,392 @@
+use std::collections::VecDeque;
+
+use chrono::{Duration, NaiveDateTime, NaiveTime, TimeDelta};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    app::AppEvent,
+    framework::{info::FrameworkInfo, MAX_CHARGE_LIMIT_MIN},
+};
+
+/// Samples older than this don't count towards the observed charge rate
+const RATE_WINDOW_MINUTES: i64 = 30;
+/// Minimum span of samples before the observed rate is trusted
+const RATE_MIN_SPAN_MINUTES: i64 = 5;
+/// Give up on a raised plan if the charger is still attached this long after the deadline
+const RESTORE_AFTER_DEADLINE_HOURS: i64 = 12;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmartChargeConfig {
+    /// Default deadline for plans started from the TUI
+    pub full_by: NaiveTime,
+    #[serde(deserialize_with = "deserialize_target")]
+    pub target: u8,
+    /// Extra time to account for the slower charging close to full
+    pub margin_minutes: u32,
+    /// Charge rate in percent per hour used until a real one is observed
+    pub fallback_rate: f32,
+}
+
+impl Default for SmartChargeConfig {
+    fn default() -> Self {
+        Self {
+            full_by: NaiveTime::from_hms_opt(8, 0, 0).unwrap_or_default(),
+            target: 100,
+            margin_minutes: 30,
+            fallback_rate: 30.0,
+        }
+    }
+}
+
+/// The target becomes the max charge limit, so it follows the same bounds
+fn deserialize_target<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    let target = u8::deserialize(deserializer)?;
+
+    if target > 100 {
+        return Err(D::Error::custom(format!("target {} is over 100", target)));
+    }
+    if target < MAX_CHARGE_LIMIT_MIN {
+        return Err(D::Error::custom(format!(
+            "target {} is below {}",
+            target, MAX_CHARGE_LIMIT_MIN
+        )));
+    }
+
+    Ok(target)
+}
+
+/// "Full by the deadline" plan, kept in the state file so that an app restart
+/// still restores the normal limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChargePlan {
+    pub deadline: NaiveDateTime,
+    pub target: u8,
+    pub normal_limit: u8,
+    pub raised: bool,
+}
+
+/// Holds the normal charge limit and raises it just in time to reach the
+/// target by the deadline, then puts it back once the charger is unplugged.
+pub struct SmartCharger {
+    config: SmartChargeConfig,
+    plan: Option<ChargePlan>,
+    samples: VecDeque<(NaiveDateTime, u32)>,
+    observed_rate: Option<f32>,
+}
+
+impl SmartCharger {
+    pub fn new(config: SmartChargeConfig, plan: Option<ChargePlan>) -> Self {
+        Self {
+            config,
+            plan,
+            samples: VecDeque::new(),
+            observed_rate: None,
+        }
+    }
+
+    pub fn plan(&self) -> Option<&ChargePlan> {
+        self.plan.as_ref()
+    }
+
+    /// Plan a full charge by the next `full_by`, or by the default deadline
+    pub fn schedule(
+        &mut self,
+        full_by: Option<NaiveTime>,
+        info: &FrameworkInfo,
+        now: NaiveDateTime,
+    ) -> Option<AppEvent> {
+        let restore = self.cancel();
+        let normal_limit = match restore {
+            Some(AppEvent::SetMaxChargeLimit(limit)) => limit,
+            _ => info.max_charge_limit.unwrap_or(self.config.target),
+        };
+
+        self.plan = Some(ChargePlan {
+            deadline: next_occurrence(full_by.unwrap_or(self.config.full_by), now),
+            target: self.config.target,
+            normal_limit,
+            raised: false,
+        });
+
+        restore
+    }
+
+    /// Drop the plan, returns the event restoring the normal limit if it was raised
+    pub fn cancel(&mut self) -> Option<AppEvent> {
+        let plan = self.plan.take()?;
+
+        plan.raised
+            .then_some(AppEvent::SetMaxChargeLimit(plan.normal_limit))
+    }
+
+    /// Make `limit` the one put back after the plan, e.g. when the user changes it
+    /// while the plan is pending. Returns whether there was a plan to update
+    pub fn set_normal_limit(&mut self, limit: u8) -> bool {
+        match self.plan.as_mut() {
+            Some(plan) => {
+                plan.normal_limit = limit;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Record the snapshot and return the limit change the plan calls for, if any
+    pub fn tick(&mut self, info: &FrameworkInfo, now: NaiveDateTime) -> Option<AppEvent> {
+        self.observe(info, now);
+
+        let plan = self.plan.as_mut()?;
+
+        if plan.raised {
+            let expired = now - plan.deadline > Duration::hours(RESTORE_AFTER_DEADLINE_HOURS);
+
+            if !info.is_ac_connected || expired {
+                return self.cancel();
+            }
+
+            return None;
+        }
+
+        if now >= plan.deadline {
+            // NOTE: never got the chance to charge, nothing to restore
+            self.plan = None;
+            return None;
+        }
+
+        let start_at = self.start_at(info)?;
+        let plan = self.plan.as_mut()?;
+
+        if now >= start_at && info.is_ac_connected {
+            plan.raised = true;
+
+            return Some(AppEvent::SetMaxChargeLimit(plan.target));
+        }
+
+        None
+    }
+
+    /// Human readable state of the plan for the Charge panel
+    pub fn status(&self, info: &FrameworkInfo) -> Option<String> {
+        let plan = self.plan.as_ref()?;
+        let deadline = plan.deadline.format("%H:%M");
+
+        let status = if plan.raised {
+            format!(
+                "{}% by {}, back to {}% when unplugged",
+                plan.target, deadline, plan.normal_limit
+            )
+        } else {
+            match self.start_at(info) {
+                Some(start_at) => format!(
+                    "{}% by {}, charging from {}",
+                    plan.target,
+                    deadline,
+                    start_at.format("%H:%M")
+                ),
+                None => format!("{}% by {}", plan.target, deadline),
+            }
+        };
+
+        Some(status)
+    }
+
+    /// Charge rate in percent per hour
+    pub fn rate(&self) -> f32 {
+        self.observed_rate.unwrap_or(self.config.fallback_rate)
+    }
+
+    fn start_at(&self, info: &FrameworkInfo) -> Option<NaiveDateTime> {
+        let plan = self.plan.as_ref()?;
+        let charge = info.charge_percentage?;
+        let remaining = (plan.target as u32).saturating_sub(charge) as f32;
+        let hours = remaining / self.rate().max(1.0);
+        let needed = TimeDelta::seconds((hours * 3600.0) as i64)
+            + Duration::minutes(self.config.margin_minutes as i64);
+
+        Some(plan.deadline - needed)
+    }
+
+    fn observe(&mut self, info: &FrameworkInfo, now: NaiveDateTime) {
+        let Some(charge) = info.charge_percentage.filter(|_| info.is_charging) else {
+            self.samples.clear();
+            return;
+        };
+
+        self.samples.push_back((now, charge));
+        while let Some((time, _)) = self.samples.front() {
+            if now - *time > Duration::minutes(RATE_WINDOW_MINUTES) {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if let (Some((first_time, first_charge)), Some((last_time, last_charge))) =
+            (self.samples.front(), self.samples.back())
+        {
+            let span = *last_time - *first_time;
+
+            if span >= Duration::minutes(RATE_MIN_SPAN_MINUTES) && last_charge > first_charge {
+                let hours = span.num_seconds() as f32 / 3600.0;
+
+                self.observed_rate = Some((last_charge - first_charge) as f32 / hours);
+            }
+        }
+    }
+}
+
+/// Next time the clock shows `time`, today or tomorrow
+pub fn next_occurrence(time: NaiveTime, now: NaiveDateTime) -> NaiveDateTime {
+    let today = now.date().and_time(time);
+
+    if today > now {
+        today
+    } else {
+        today + Duration::days(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+    use crate::{
+        app::AppEvent,
+        framework::info::FrameworkInfo,
+        smart_charge::{SmartChargeConfig, SmartCharger},
+    };
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn plugged_in(charge: u32, is_charging: bool) -> FrameworkInfo {
+        FrameworkInfo {
+            charge_percentage: Some(charge),
+            max_charge_limit: Some(80),
+            is_ac_connected: true,
+            is_charging,
+            ..FrameworkInfo::default()
+        }
+    }
+
+    #[test]
+    fn raises_the_limit_just_in_time_and_restores_it_after_unplugging() {
+        // 20% to go at 30%/h plus 30 minutes of margin: start 70 minutes before 08:00
+        let mut charger = SmartCharger::new(SmartChargeConfig::default(), None);
+        let info = plugged_in(80, false);
+
+        assert_eq!(charger.schedule(None, &info, at(1, 22, 0)), None);
+        assert_eq!(charger.plan().unwrap().deadline, at(2, 8, 0));
+        assert_eq!(
+            charger.status(&info).unwrap(),
+            "100% by 08:00, charging from 06:50"
+        );
+
+        assert_eq!(charger.tick(&info, at(2, 6, 49)), None);
+        assert_eq!(
+            charger.tick(&info, at(2, 6, 50)),
+            Some(AppEvent::SetMaxChargeLimit(100))
+        );
+        assert_eq!(charger.tick(&plugged_in(100, false), at(2, 9, 0)), None);
+
+        let unplugged = FrameworkInfo {
+            is_ac_connected: false,
+            ..plugged_in(100, false)
+        };
+        assert_eq!(
+            charger.tick(&unplugged, at(2, 9, 30)),
+            Some(AppEvent::SetMaxChargeLimit(80))
+        );
+        assert!(charger.plan().is_none());
+    }
+
+    #[test]
+    fn uses_the_observed_charge_rate() {
+        let mut charger = SmartCharger::new(SmartChargeConfig::default(), None);
+        let start = at(1, 12, 0);
+
+        // 10% in 10 minutes
+        for minute in 0..=10 {
+            let info = plugged_in(50 + minute, true);
+            charger.tick(&info, start + Duration::minutes(minute as i64));
+        }
+
+        assert_eq!(charger.rate(), 60.0);
+    }
+
+    #[test]
+    fn cancel_restores_a_raised_limit_and_stale_plans_expire() {
+        let mut charger = SmartCharger::new(SmartChargeConfig::default(), None);
+        let info = plugged_in(80, false);
+        let full_by = NaiveTime::from_hms_opt(7, 0, 0);
+
+        charger.schedule(full_by, &info, at(1, 6, 0));
+        assert_eq!(
+            charger.tick(&info, at(1, 6, 0)),
+            Some(AppEvent::SetMaxChargeLimit(100))
+        );
+        assert_eq!(charger.cancel(), Some(AppEvent::SetMaxChargeLimit(80)));
+
+        // Unplugged the whole night, the deadline passes without changing anything
+        let unplugged = FrameworkInfo {
+            is_ac_connected: false,
+            ..info
+        };
+        charger.schedule(full_by, &unplugged, at(1, 6, 30));
+        assert_eq!(charger.tick(&unplugged, at(1, 6, 45)), None);
+        assert_eq!(charger.tick(&unplugged, at(1, 7, 0)), None);
+        assert!(charger.plan().is_none());
+    }
+
+    #[test]
+    fn restores_the_limit_chosen_while_the_plan_is_pending() {
+        let mut charger = SmartCharger::new(SmartChargeConfig::default(), None);
+        let info = plugged_in(60, false);
+
+        assert!(!charger.set_normal_limit(70));
+        charger.schedule(NaiveTime::from_hms_opt(7, 0, 0), &info, at(1, 22, 0));
+        assert!(charger.set_normal_limit(70));
+        assert_eq!(
+            charger.tick(&info, at(2, 6, 0)),
+            Some(AppEvent::SetMaxChargeLimit(100))
+        );
+
+        let unplugged = FrameworkInfo {
+            is_ac_connected: false,
+            ..info
+        };
+        assert_eq!(
+            charger.tick(&unplugged, at(2, 8, 0)),
+            Some(AppEvent::SetMaxChargeLimit(70))
+        );
+    }
+
+    #[test]
+    fn targets_that_cant_be_applied_are_rejected() {
+        let parse = |content: &str| {
+            toml::from_str::<crate::config::Config>(content)
+                .map_err(|error| error.message().to_string())
+        };
+
+        assert_eq!(
+            parse("[smart_charge]\ntarget = 120").unwrap_err(),
+            "target 120 is over 100"
+        );
+        assert_eq!(
+            parse("[smart_charge]\ntarget = 10").unwrap_err(),
+            "target 10 is below 25"
+        );
+        assert_eq!(
+            parse("[smart_charge]\ntarget = 90")
+                .unwrap()
+                .smart_charge
+                .target,
+            90
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{paths, smart_charge::ChargePlan};
+
+/// Runtime state that has to survive restarts but isn't user configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    pub charge_plan: Option<ChargePlan>,
+    pub active_profile: Option<String>,
+}
+
+impl State {
+    /// Get the state file path
+    fn state_path() -> color_eyre::Result<PathBuf> {
+        Ok(paths::data_dir()?.join("state.json"))
+    }
+
+    /// Load the state, a missing or broken file starts from scratch
+    pub fn load() -> Self {
+        Self::state_path()
+            .and_then(|path| Ok(fs::read_to_string(path)?))
+            .and_then(|content| Ok(serde_json::from_str(&content)?))
+            .unwrap_or_default()
+    }
+
+    /// Save state to file
+    pub fn save(&self) -> color_eyre::Result<()> {
+        let state_path = Self::state_path()?;
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&state_path, content)?;
+        paths::hand_over(&state_path)?;
+        Ok(())
+    }
+
+    /// Update the charge plan and save
+    pub fn set_charge_plan(&mut self, charge_plan: Option<ChargePlan>) -> color_eyre::Result<()> {
+        self.charge_plan = charge_plan;
+        self.save()
+    }
+
+    /// Update the active profile and save
+    pub fn set_active_profile(&mut self, active_profile: Option<String>) -> color_eyre::Result<()> {
+        self.active_profile = active_profile;
+        self.save()
+    }
+}
@@ -0,0 +1,258 @@
+use std::{io::Write, time::Duration};
+
+use serde::Serialize;
+
+use crate::framework::{info::FrameworkInfo, Hardware};
+
+pub const DEFAULT_TEMPLATE: &str = "{charge}% {status} {power}W";
+pub const DEFAULT_WAYBAR_TEMPLATE: &str = "{charge}%";
+
+/// Charge level at which waybar gets the `warning` class
+const WAYBAR_WARNING_PERCENTAGE: u32 = 30;
+
+/// Single line output for status bars such as waybar, i3status-rs or tmux
+pub enum StatusOutput {
+    /// Plain text rendered from a template
+    Template(String),
+    /// Waybar custom module JSON, the template fills in the `text` field
+    Waybar(String),
+}
+
+#[derive(Serialize)]
+struct WaybarOutput {
+    text: String,
+    tooltip: String,
+    class: Vec<&'static str>,
+    percentage: u32,
+}
+
+impl StatusOutput {
+    pub fn render(&self, info: &FrameworkInfo) -> String {
+        match self {
+            StatusOutput::Template(template) => render_template(template, info),
+            StatusOutput::Waybar(template) => render_waybar(template, info),
+        }
+    }
+}
+
+/// Print one line per tick, or a single line when `once` is set
+pub async fn run(
+    mut hardware: Box<dyn Hardware>,
+    output: StatusOutput,
+    once: bool,
+    interval: Duration,
+) -> color_eyre::Result<()> {
+    let mut tick = tokio::time::interval(interval);
+    let mut stdout = std::io::stdout();
+
+    loop {
+        tick.tick().await;
+
+        let info = hardware.get_info()?;
+
+        // NOTE: the bar went away, nothing left to print to
+        if writeln!(stdout, "{}", output.render(&info)).is_err() {
+            break;
+        }
+
+        if once {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace `{placeholder}`s in `template` with values from `info`.
+///
+/// Unknown placeholders are kept as is, `{{` and `}}` produce literal braces.
+pub fn render_template(template: &str, info: &FrameworkInfo) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                let mut closed = false;
+
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    name.push(c);
+                }
+
+                match placeholder_value(&name, info) {
+                    Some(value) if closed => output.push_str(&value),
+                    _ => {
+                        output.push('{');
+                        output.push_str(&name);
+                        if closed {
+                            output.push('}');
+                        }
+                    }
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+fn placeholder_value(name: &str, info: &FrameworkInfo) -> Option<String> {
+    let value = match name {
+        "charge" => optional(info.charge_percentage),
+        "status" => info.charging_status.clone(),
+        "power" => info
+            .battery_power_watts()
+            .map(|power| format!("{:.1}", power))
+            .unwrap_or_else(na),
+        "limit" => optional(info.max_charge_limit),
+        "voltage" => optional(info.charger_voltage),
+        "current" => optional(info.charger_current),
+        "cycles" => optional(info.cycle_count),
+        "capacity_loss" => info
+            .capacity_loss_percentage
+            .map(|loss| format!("{:.1}", loss))
+            .unwrap_or_else(na),
+        "ac" => on_off(info.is_ac_connected),
+        "mic" => on_off(info.is_microphone_enabled),
+        "camera" => on_off(info.is_camera_enabled),
+        "kb" => optional(info.kb_brightness_percentage),
+        "fp" => optional(info.fp_brightness_percentage),
+        "bios" => info.smbios_version.clone().unwrap_or_else(na),
+        "fans" => match &info.fan_rpm {
+            Some(fan_rpm) => fan_rpm
+                .iter()
+                .map(|rpm| rpm.to_string())
+                .collect::<Vec<String>>()
+                .join(" "),
+            None => na(),
+        },
+        _ => {
+            // {fan1}, {fan2}, ...
+            let index = name.strip_prefix("fan")?.parse::<usize>().ok()?;
+
+            info.fan_rpm
+                .as_ref()
+                .and_then(|fan_rpm| fan_rpm.get(index.checked_sub(1)?))
+                .map(|rpm| rpm.to_string())
+                .unwrap_or_else(na)
+        }
+    };
+
+    Some(value)
+}
+
+fn render_waybar(template: &str, info: &FrameworkInfo) -> String {
+    let mut class = Vec::new();
+
+    class.push(match (info.is_charging, info.is_ac_connected) {
+        (true, _) => "charging",
+        (false, true) => "full",
+        (false, false) => "discharging",
+    });
+
+    match info.charge_percentage {
+        Some(_) if info.is_charge_low() => class.push("critical"),
+        Some(charge_percentage) if charge_percentage < WAYBAR_WARNING_PERCENTAGE => {
+            class.push("warning")
+        }
+        _ => {}
+    }
+
+    let tooltip = render_template(
+        "{status} {charge}%\nMax charge limit: {limit}%\nPower: {power} W\nFans: {fans} RPM\nCycles: {cycles}",
+        info,
+    );
+    let output = WaybarOutput {
+        text: render_template(template, info),
+        tooltip,
+        class,
+        percentage: info.charge_percentage.unwrap_or(0),
+    };
+
+    // NOTE: serializing a struct of strings and numbers can't fail
+    serde_json::to_string(&output).unwrap_or_default()
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_else(na)
+}
+
+/// How switches are written in status templates and hook variables
+pub fn on_off(value: bool) -> String {
+    if value { "on" } else { "off" }.to_string()
+}
+
+fn na() -> String {
+    "N/A".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        framework::info::FrameworkInfo,
+        status::{render_template, StatusOutput},
+    };
+
+    fn info() -> FrameworkInfo {
+        FrameworkInfo {
+            charge_percentage: Some(72),
+            charging_status: "Charging".to_string(),
+            charger_voltage: Some(17_000),
+            charger_current: Some(2_000),
+            max_charge_limit: Some(80),
+            fan_rpm: Some(vec![2400, 1800]),
+            is_charging: true,
+            is_ac_connected: true,
+            ..FrameworkInfo::default()
+        }
+    }
+
+    #[test]
+    fn render_template_replaces_placeholders() {
+        let line = render_template("{charge}% {status} {power}W {fan1}rpm {fan2}rpm", &info());
+
+        assert_eq!(line, "72% Charging 34.0W 2400rpm 1800rpm");
+    }
+
+    #[test]
+    fn render_template_keeps_unknown_placeholders_and_escapes() {
+        let line = render_template("{{{charge}}} {nope} {fan3} {unclosed", &info());
+
+        assert_eq!(line, "{72} {nope} N/A {unclosed");
+    }
+
+    #[test]
+    fn waybar_output_sets_class_from_charge_and_state() {
+        let mut info = info();
+        let output = StatusOutput::Waybar("{charge}%".to_string());
+
+        let json: serde_json::Value = serde_json::from_str(&output.render(&info)).unwrap();
+        assert_eq!(json["text"], "72%");
+        assert_eq!(json["percentage"], 72);
+        assert_eq!(json["class"], serde_json::json!(["charging"]));
+
+        info.is_charging = false;
+        info.is_ac_connected = false;
+        info.charge_percentage = Some(10);
+
+        let json: serde_json::Value = serde_json::from_str(&output.render(&info)).unwrap();
+        assert_eq!(
+            json["class"],
+            serde_json::json!(["discharging", "critical"])
+        );
+    }
+}
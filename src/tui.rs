@@ -1,38 +1,65 @@
 pub mod component;
 pub mod control;
+pub mod keymap;
+pub mod layout;
 pub mod theme;
 
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use ratatui::{
-    crossterm::event::{Event, KeyCode, KeyEventKind},
-    layout::{Constraint, Flex, Layout},
+    crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind},
+    layout::{Constraint, Flex, Layout, Rect},
     prelude::Backend,
     style::Style,
     text::Text,
-    widgets::Block,
+    widgets::{Block, Borders, Clear, Paragraph},
     Frame, Terminal,
 };
 use tui_popup::Popup;
 
 use crate::{
     app::AppEvent,
-    config::Config,
+    config::{layer::Machine, Config},
     framework::{fingerprint::Fingerprint, info::FrameworkInfo},
+    rules::{RuleStatus, RulesEngine},
     tui::{
         component::{
-            footer::FooterComponent, main::MainComponent, title::TitleComponent, Component,
+            footer::FooterComponent,
+            help::HelpComponent,
+            main::MainComponent,
+            profile_picker::ProfilePickerComponent,
+            rules_panel::RulesPanelComponent,
+            theme_picker::{ThemePick, ThemePickerComponent},
+            title::TitleComponent,
+            Component,
         },
-        theme::Theme,
+        keymap::{Action, Keymap},
+        layout::{LayoutMode, MIN_HEIGHT, MIN_WIDTH},
+        theme::{custom::Themes, palette::ColorSupport, Theme, ThemeId},
     },
 };
 
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
 pub struct Tui {
     pub title: TitleComponent,
     main: MainComponent,
     footer: FooterComponent,
+    rules_panel: RulesPanelComponent,
+    profile_picker: ProfilePickerComponent,
+    theme_picker: ThemePickerComponent,
+    help: HelpComponent,
     theme: Theme,
+    themes: Themes,
+    color_support: ColorSupport,
+    keymap: Keymap,
+    /// Layout of the last frame, none when the terminal was too small
+    layout: Option<LayoutMode>,
     error_message: Option<String>,
+    toast: Option<(String, Instant)>,
     config: Config,
     tick_interval_ms: u64,
 }
@@ -43,35 +70,114 @@ impl Tui {
         info: &FrameworkInfo,
         config: Config,
     ) -> color_eyre::Result<Self> {
-        let theme = Theme::from_variant(config.theme);
+        let themes = Themes::default();
+        let color_support = config.color_support.resolve();
+        let theme = themes
+            .get(config.theme)
+            .unwrap_or_default()
+            .downsample(color_support);
+        let rules_panel =
+            RulesPanelComponent::new(RulesEngine::new(config.rules.clone()).statuses());
+        // NOTE: conflicting keys would make some actions unreachable, use the defaults instead
+        let (keymap, error_message) = match config.keys.check() {
+            Ok(()) => (config.keys.clone(), None),
+            Err(e) => (Keymap::default(), Some(e)),
+        };
+
+        let mut main = MainComponent::new(fingerprint, info);
+        main.set_dashboard(config.dashboard.clone());
 
         Ok(Self {
-            title: TitleComponent::new(theme.variant),
-            main: MainComponent::new(fingerprint, info),
-            footer: FooterComponent,
+            title: TitleComponent::new(config.theme),
+            main,
+            footer: FooterComponent::new(keymap.help()),
+            rules_panel,
+            profile_picker: ProfilePickerComponent::new(config.profiles.keys().cloned().collect()),
+            theme_picker: ThemePickerComponent::new(),
+            help: HelpComponent::new(),
             theme,
-            error_message: None,
+            themes,
+            color_support,
+            keymap,
+            layout: Some(LayoutMode::Standard),
+            error_message,
+            toast: None,
             tick_interval_ms: config.tick_interval_ms,
             config,
         })
     }
 
     pub fn next_theme(&mut self) {
-        let next_variant = self.config.theme.next();
-        self.theme = Theme::from_variant(next_variant);
-        if let Err(e) = self.config.set_theme(next_variant) {
-            self.set_error(format!("Failed to save theme: {}", e));
-        }
+        self.switch_theme(self.themes.next(self.config.theme));
     }
 
     pub fn previous_theme(&mut self) {
-        let prev_variant = self.config.theme.previous();
-        self.theme = Theme::from_variant(prev_variant);
-        if let Err(e) = self.config.set_theme(prev_variant) {
+        self.switch_theme(self.themes.previous(self.config.theme));
+    }
+
+    fn switch_theme(&mut self, id: ThemeId) {
+        self.theme = self.colors(id).unwrap_or_default();
+        if let Err(e) = self.config.set_theme(id) {
             self.set_error(format!("Failed to save theme: {}", e));
         }
     }
 
+    /// Replace the custom themes, e.g. after loading them from the themes directory
+    pub fn set_themes(&mut self, themes: Themes) {
+        self.themes = themes;
+        self.apply_theme();
+    }
+
+    /// Colours of `id` reduced to what the terminal supports
+    fn colors(&self, id: ThemeId) -> Option<Theme> {
+        self.themes
+            .get(id)
+            .map(|theme| theme.downsample(self.color_support))
+    }
+
+    /// Use the colours of the configured theme, falling back to the default
+    /// theme when it's a custom theme that isn't loaded
+    fn apply_theme(&mut self) {
+        match self.colors(self.config.theme) {
+            Some(theme) => self.theme = theme,
+            None => {
+                self.theme = Theme::default().downsample(self.color_support);
+                self.set_error(format!("Unknown theme {}", self.config.theme.name()));
+            }
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Read the config files again, e.g. after they were edited
+    pub fn reload_config(&mut self, machine: &Machine) -> color_eyre::Result<()> {
+        let config = self.config.reload(machine)?;
+
+        self.tick_interval_ms = config.tick_interval_ms;
+        self.color_support = config.color_support.resolve();
+        match config.keys.check() {
+            Ok(()) => {
+                self.keymap = config.keys.clone();
+                self.footer.set_help(self.keymap.help());
+            }
+            Err(e) => self.set_error(e),
+        }
+        self.profile_picker
+            .set_names(config.profiles.keys().cloned().collect());
+        self.main.set_dashboard(config.dashboard.clone());
+        self.config = config;
+
+        let (themes, errors) = Themes::load();
+        self.set_themes(themes);
+        if !errors.is_empty() {
+            self.set_error(errors.join("; "));
+        }
+
+        Ok(())
+    }
+
     pub fn current_theme_name(&self) -> &'static str {
         self.config.theme.name()
     }
@@ -96,7 +202,74 @@ impl Tui {
         }
     }
 
+    pub fn set_charge_plan_status(&mut self, status: Option<String>) {
+        self.main.set_charge_plan_status(status);
+    }
+
+    pub fn set_active_profile(&mut self, profile: Option<String>) {
+        self.profile_picker.set_active(profile);
+    }
+
+    pub fn record_history(&mut self, info: &FrameworkInfo) {
+        self.main.record_history(info);
+    }
+
+    pub fn set_rule_statuses(&mut self, statuses: Vec<RuleStatus>) {
+        self.rules_panel.set_statuses(statuses);
+    }
+
     pub fn handle_input(&mut self, event: Event) -> color_eyre::Result<Option<AppEvent>> {
+        if self.theme_picker.is_visible() && self.error_message.is_none() {
+            // NOTE: plain characters go to the search filter instead of their actions
+            let typed = matches!(&event, Event::Key(key)
+                if matches!(key.code, KeyCode::Char(_))
+                    && !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT));
+            let event = if typed {
+                Some(event)
+            } else {
+                self.keymap.translate(event)
+            };
+
+            match event.and_then(|event| self.theme_picker.handle_key(event)) {
+                Some(ThemePick::Preview(id)) | Some(ThemePick::Revert(id)) => {
+                    self.theme = self.colors(id).unwrap_or_default();
+                }
+                Some(ThemePick::Commit(id)) => self.switch_theme(id),
+                None => {}
+            }
+
+            return Ok(None);
+        }
+
+        // Components handle every action by its default key
+        let Some(event) = self.keymap.translate(event) else {
+            return Ok(None);
+        };
+        // NOTE: nothing is drawn to click on while the terminal is too small
+        if self.layout.is_none() && matches!(event, Event::Mouse(_)) {
+            return Ok(None);
+        }
+
+        if self.help.is_visible() && self.error_message.is_none() {
+            return Ok(self.help.handle_input(event));
+        }
+
+        if self.rules_panel.is_visible() && self.error_message.is_none() {
+            let app_event = self.rules_panel.handle_input(event);
+
+            if let Some(AppEvent::SetRuleEnabled(index, enabled)) = app_event {
+                if let Some(rule) = self.config.rules.get_mut(index) {
+                    rule.enabled = enabled;
+                }
+            }
+
+            return Ok(app_event);
+        }
+
+        if self.profile_picker.is_visible() && self.error_message.is_none() {
+            return Ok(self.profile_picker.handle_input(event));
+        }
+
         let top_level_event = match &event {
             Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
                 KeyCode::Char('q') => Some(AppEvent::Quit),
@@ -110,12 +283,53 @@ impl Tui {
                 }
                 KeyCode::Char('+') | KeyCode::Char('=') => self.increase_tick_interval(),
                 KeyCode::Char('-') => self.decrease_tick_interval(),
+                KeyCode::Char('r') => {
+                    self.rules_panel.toggle();
+                    None
+                }
+                KeyCode::Char('p') => {
+                    self.profile_picker.toggle();
+                    None
+                }
+                KeyCode::Char('?') => {
+                    self.help.open(&self.keymap, self.main.help_context());
+                    None
+                }
+                KeyCode::Char('t') => {
+                    let themes = self
+                        .themes
+                        .ids()
+                        .into_iter()
+                        .filter_map(|id| self.colors(id))
+                        .collect();
+                    self.theme_picker.open(themes, self.config.theme);
+                    None
+                }
+                // NOTE: while a control is edited the digits type its value
+                KeyCode::Char(c @ '1'..='9') if !self.main.is_editing() => self
+                    .profile_picker
+                    .by_number(c.to_digit(10).unwrap_or_default() as usize),
                 KeyCode::Esc if self.error_message.is_some() => {
                     self.error_message = None;
                     None
                 }
                 _ => None,
             },
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                match self.title.click(mouse.column, mouse.row) {
+                    Some(Action::PreviousTheme) => {
+                        self.previous_theme();
+                        None
+                    }
+                    Some(Action::NextTheme) => {
+                        self.next_theme();
+                        None
+                    }
+                    Some(Action::IncreaseTickInterval) => self.increase_tick_interval(),
+                    Some(Action::DecreaseTickInterval) => self.decrease_tick_interval(),
+                    _ => None,
+                }
+            }
             _ => None,
         };
 
@@ -142,10 +356,17 @@ impl Tui {
             frame.render_widget(block, frame.area());
 
             let area = frame.area();
+            self.layout = LayoutMode::for_area(area);
+            let Some(layout) = self.layout else {
+                Self::render_too_small(frame, area);
+                return;
+            };
+            self.main.set_layout(layout);
+
             let [area] = Layout::vertical([Constraint::Max(49)])
                 .flex(Flex::Center)
                 .areas(area);
-            let [area] = Layout::horizontal([Constraint::Max(140)])
+            let [area] = Layout::horizontal([Constraint::Max(layout.max_width())])
                 .flex(Flex::Center)
                 .areas(area);
 
@@ -165,6 +386,32 @@ impl Tui {
             // Footer
             self.footer.render(frame, footer_area, &self.theme, info);
 
+            // Profile picker popup
+            if self.profile_picker.is_visible() {
+                self.profile_picker
+                    .render(frame, frame.area(), &self.theme, info);
+            }
+
+            // Theme picker popup, the dashboard behind it shows the selected theme
+            if self.theme_picker.is_visible() {
+                self.theme_picker
+                    .render(frame, frame.area(), &self.theme, info);
+            }
+
+            // Help popup
+            if self.help.is_visible() {
+                self.help.render(frame, frame.area(), &self.theme, info);
+            }
+
+            // Rules popup
+            if self.rules_panel.is_visible() {
+                self.rules_panel
+                    .render(frame, frame.area(), &self.theme, info);
+            }
+
+            // Toast, if there is a fresh one
+            self.render_toast(frame);
+
             // Error popup if error is set
             self.render_error_popup(frame);
         })?;
@@ -176,6 +423,56 @@ impl Tui {
         self.error_message = Some(message);
     }
 
+    /// Show a short-lived notification in the top right corner
+    pub fn show_toast(&mut self, message: String) {
+        self.toast = Some((message, Instant::now()));
+    }
+
+    fn render_too_small(frame: &mut Frame, area: Rect) {
+        let text = format!(
+            "Terminal too small: {}x{}\nIt needs at least {}x{}",
+            area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+        );
+        let [area] = Layout::vertical([Constraint::Length(2)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        frame.render_widget(
+            Paragraph::new(text).alignment(ratatui::layout::Alignment::Center),
+            area,
+        );
+    }
+
+    fn render_toast(&mut self, frame: &mut Frame) {
+        if let Some((_, shown_at)) = &self.toast {
+            if shown_at.elapsed() > TOAST_DURATION {
+                self.toast = None;
+            }
+        }
+
+        if let Some((message, _)) = &self.toast {
+            let area = frame.area();
+            let width = (message.chars().count() as u16 + 4).min(area.width);
+            let height = 3.min(area.height);
+            let toast_area = Rect {
+                x: area.x + area.width - width,
+                y: area.y,
+                width,
+                height,
+            };
+            let toast = Paragraph::new(format!(" {} ", message))
+                .style((self.theme.indication_warning, self.theme.background))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(self.theme.border),
+                );
+
+            frame.render_widget(Clear, toast_area);
+            frame.render_widget(toast, toast_area);
+        }
+    }
+
     fn render_error_popup(&self, frame: &mut Frame) {
         if let Some(message) = &self.error_message {
             let mut text = Text::default();
@@ -199,7 +496,11 @@ impl Tui {
 mod tests {
     use std::sync::Arc;
 
-    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent};
+    use ratatui::{
+        backend::TestBackend,
+        crossterm::event::{Event, KeyCode, KeyEvent},
+        Terminal,
+    };
 
     use crate::{
         app::AppEvent,
@@ -221,6 +522,32 @@ mod tests {
         assert!(matches!(app_event, Ok(Some(AppEvent::Quit))))
     }
 
+    #[test]
+    fn render_fits_the_terminal_or_says_it_is_too_small() {
+        let fingerprint = Arc::new(Fingerprint::percentage());
+        let info = FrameworkInfo::default();
+        let mut tui = Tui::new(fingerprint, &info, Config::default()).unwrap();
+        let screen = |width, height, tui: &mut Tui| {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            tui.render(&mut terminal, &info).unwrap();
+
+            terminal.backend().to_string()
+        };
+
+        assert!(screen(40, 12, &mut tui).contains("Terminal too small: 40x12"));
+
+        let compact = screen(80, 24, &mut tui);
+        assert!(compact.contains("Limit"));
+        assert!(compact.contains(" Brightness "));
+        assert!(!compact.contains(" BIOS "));
+
+        let standard = screen(120, 40, &mut tui);
+        assert!(standard.contains("Max charge limit"));
+        assert!(!standard.contains(" History "));
+
+        assert!(screen(200, 50, &mut tui).contains(" History "));
+    }
+
     #[test]
     fn next_theme_cycles_forward() {
         let fingerprint = Arc::new(Fingerprint::percentage());
@@ -1,5 +1,10 @@
 pub mod pd_ports_panel;
-use ratatui::{crossterm::event::Event, layout::Rect, prelude::*, Frame};
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind},
+    layout::{Position, Rect},
+    prelude::*,
+    Frame,
+};
 
 use crate::{
     app::AppEvent,
@@ -10,9 +15,14 @@ use crate::{
 pub mod brightness_panel;
 pub mod charge_panel;
 pub mod footer;
+pub mod help;
+pub mod history_panel;
 pub mod main;
 pub mod privacy_panel;
+pub mod profile_picker;
+pub mod rules_panel;
 pub mod smbios_panel;
+pub mod theme_picker;
 pub mod title;
 
 pub trait Component {
@@ -31,9 +41,27 @@ pub struct AdjustablePanel {
     selected: bool,
     controls: Vec<AdjustableControl>,
     selected_control: usize,
+    /// Where every control was last drawn, for the mouse
+    control_areas: Vec<Rect>,
+    /// Digits typed into the focused control, `None` until the first one
+    input: Option<String>,
+    /// Drawn without spacing and with abbreviated labels
+    compact: bool,
 }
 
 impl AdjustablePanel {
+    pub fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    fn label(&self, full: &'static str, abbreviated: &'static str) -> &'static str {
+        if self.compact {
+            abbreviated
+        } else {
+            full
+        }
+    }
+
     fn toggle(&mut self) {
         self.selected = !self.is_selected();
     }
@@ -62,7 +90,44 @@ impl AdjustablePanel {
         }
     }
 
+    fn set_control_area(&mut self, index: usize, area: Rect) {
+        if self.control_areas.len() <= index {
+            self.control_areas.resize(index + 1, Rect::default());
+        }
+        self.control_areas[index] = area;
+    }
+
+    /// Key a mouse event over a control stands for: a click edits or applies
+    /// the control like Enter, the wheel adjusts the focused control
+    fn mouse_to_key(&mut self, mouse: MouseEvent) -> Option<Event> {
+        let position = Position::new(mouse.column, mouse.row);
+        let index = self
+            .control_areas
+            .iter()
+            .position(|area| area.contains(position))?;
+        let focused = index == self.selected_control && self.get_selected_control().is_focused();
+
+        let code = match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if index != self.selected_control {
+                    if self.get_selected_control().is_focused() {
+                        self.toggle_selected_control_focus();
+                    }
+                    self.selected_control = index;
+                }
+
+                KeyCode::Enter
+            }
+            MouseEventKind::ScrollUp if focused => KeyCode::Right,
+            MouseEventKind::ScrollDown if focused => KeyCode::Left,
+            _ => return None,
+        };
+
+        Some(Event::Key(KeyEvent::from(code)))
+    }
+
     fn toggle_selected_control_focus(&mut self) {
+        self.input = None;
         self.controls[self.selected_control] = self.get_selected_control().toggle_focus();
     }
 
@@ -70,12 +135,58 @@ impl AdjustablePanel {
         if let Some(AdjustableControl::Percentage(focused, value)) =
             self.get_selected_and_focused_control()
         {
-            let new_value = *value as i8 + delta;
+            let new_value = (*value as i16 + delta as i16).clamp(0, 100);
 
-            if (0..=100).contains(&new_value) {
-                self.controls[self.selected_control] =
-                    AdjustableControl::Percentage(*focused, new_value as u8);
-            }
+            self.controls[self.selected_control] =
+                AdjustableControl::Percentage(*focused, new_value as u8);
+            self.input = None;
+        }
+    }
+
+    /// Type a digit into the focused percentage control, values over 100 are capped
+    fn enter_digit(&mut self, digit: char) {
+        let input = self.input.get_or_insert_default();
+
+        if input.len() < 3 {
+            input.push(digit);
+        }
+        self.set_focused_value_from_input();
+    }
+
+    fn erase_digit(&mut self) {
+        if let Some(input) = &mut self.input {
+            input.pop();
+        }
+        self.set_focused_value_from_input();
+    }
+
+    /// Whether every typed digit was erased again, leaving no value to apply
+    fn is_input_erased(&self) -> bool {
+        self.input.as_deref() == Some("")
+    }
+
+    fn set_focused_value_from_input(&mut self) {
+        let Some(input) = &self.input else {
+            return;
+        };
+
+        if let Some(AdjustableControl::Percentage(focused, _)) =
+            self.get_selected_and_focused_control()
+        {
+            let value = input.parse::<u16>().unwrap_or(0).min(100);
+
+            self.controls[self.selected_control] =
+                AdjustableControl::Percentage(*focused, value as u8);
+        }
+    }
+
+    /// Replace the value of the focused percentage control, typing starts over afterwards
+    fn set_focused_value(&mut self, value: u8) {
+        if let Some(AdjustableControl::Percentage(focused, _)) =
+            self.get_selected_and_focused_control()
+        {
+            self.controls[self.selected_control] = AdjustableControl::Percentage(*focused, value);
+            self.input = None;
         }
     }
 
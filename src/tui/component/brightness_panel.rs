@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use ratatui::{
-    crossterm::event::{Event, KeyCode, KeyEventKind},
+    crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers},
     layout::{Constraint, Layout, Rect},
     prelude::*,
     style::Styled,
@@ -36,6 +36,9 @@ impl BrightnessPanelComponent {
             selected: false,
             controls: vec![percentage_control(0), percentage_control(0)],
             selected_control: FINGERPRINT_BRIGHTNESS_CONTROL_INDEX,
+            control_areas: Vec::new(),
+            input: None,
+            compact: false,
         };
 
         Self { panel, fingerprint }
@@ -98,7 +101,8 @@ impl BrightnessPanelComponent {
         };
 
         frame.render_widget(
-            Paragraph::new("Fingerprint brightness").set_style(style),
+            Paragraph::new(self.panel.label("Fingerprint brightness", "Fingerprint"))
+                .set_style(style),
             key_area,
         );
         frame.render_widget(gauge, value_area);
@@ -160,24 +164,45 @@ impl BrightnessPanelComponent {
         };
 
         frame.render_widget(
-            Paragraph::new("Keyboard brightness").set_style(style),
+            Paragraph::new(self.panel.label("Keyboard brightness", "Keyboard")).set_style(style),
             key_area,
         );
         frame.render_widget(gauge, value_area);
     }
 
+    fn adjust_focused_control(&mut self, delta: i8) {
+        if self.panel.selected_control == KEYBOARD_BRIGHTNESS_CONTROL_INDEX {
+            self.panel.adjust_focused_percentage_control_by_delta(delta)
+        } else {
+            self.adjust_focused_fp_brightness_control(delta);
+        }
+    }
+
+    /// Bring a typed fingerprint brightness to a value the LED supports
+    fn validate_focused_fp_brightness_control(&mut self) {
+        if self.panel.selected_control != FINGERPRINT_BRIGHTNESS_CONTROL_INDEX {
+            return;
+        }
+        if let Some(value) = self
+            .panel
+            .get_selected_and_focused_control()
+            .and_then(AdjustableControl::get_percentage_value)
+        {
+            let value = self.fingerprint.validate_led_brightness(value);
+
+            self.panel.set_focused_value(value);
+        }
+    }
+
     fn adjust_focused_fp_brightness_control(&mut self, delta: i8) {
-        if let Some(AdjustableControl::Percentage(focused, value)) =
+        if let Some(AdjustableControl::Percentage(_, value)) =
             self.panel.get_selected_and_focused_control()
         {
             let new_value = self
                 .fingerprint
                 .adjust_led_brightness_by_delta(*value, delta);
 
-            if (0..=100).contains(&new_value) {
-                self.panel.controls[self.panel.selected_control] =
-                    AdjustableControl::Percentage(*focused, new_value);
-            }
+            self.panel.set_focused_value(new_value.min(100));
         }
     }
 }
@@ -191,6 +216,10 @@ impl AdjustableComponent for BrightnessPanelComponent {
 impl Component for BrightnessPanelComponent {
     fn handle_input(&mut self, event: Event) -> Option<crate::app::AppEvent> {
         let mut app_event = None;
+        let event = match event {
+            Event::Mouse(mouse) => self.panel.mouse_to_key(mouse)?,
+            event => event,
+        };
 
         if self.panel.is_selected() {
             if let Event::Key(key) = event {
@@ -198,7 +227,11 @@ impl Component for BrightnessPanelComponent {
                     match key.code {
                         KeyCode::Down => self.panel.cycle_controls_down(),
                         KeyCode::Up => self.panel.cycle_controls_up(),
+                        // NOTE: nothing to apply until a digit is typed again
+                        KeyCode::Enter if self.panel.is_input_erased() => {}
                         KeyCode::Enter => {
+                            self.validate_focused_fp_brightness_control();
+
                             match self.panel.get_selected_and_focused_control() {
                                 Some(control)
                                     if self.panel.selected_control
@@ -221,6 +254,16 @@ impl Component for BrightnessPanelComponent {
 
                             self.panel.toggle_selected_control_focus()
                         }
+                        KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                            self.adjust_focused_control(-1)
+                        }
+                        KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                            self.adjust_focused_control(1)
+                        }
+                        KeyCode::PageDown => self.adjust_focused_control(-10),
+                        KeyCode::PageUp => self.adjust_focused_control(10),
+                        KeyCode::Char(c @ '0'..='9') => self.panel.enter_digit(c),
+                        KeyCode::Backspace => self.panel.erase_digit(),
                         KeyCode::Left => {
                             if self.panel.selected_control == KEYBOARD_BRIGHTNESS_CONTROL_INDEX {
                                 self.panel.adjust_focused_percentage_control_by_delta(-5)
@@ -255,22 +298,32 @@ impl Component for BrightnessPanelComponent {
         let [keys_area, values_area] =
             Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
                 .horizontal_margin(2)
-                .vertical_margin(1)
+                .vertical_margin(if self.panel.compact { 0 } else { 1 })
                 .areas(block.inner(area));
+        let spacing = if self.panel.compact { 0 } else { 1 };
 
         let keys_block = Block::default().borders(Borders::NONE);
         let values_block = Block::default().borders(Borders::NONE);
 
         let [fp_brightness_key_area, kb_brightness_key_area] =
             Layout::vertical([Constraint::Length(1), Constraint::Length(1)])
-                .spacing(1)
+                .spacing(spacing)
                 .areas(keys_block.inner(keys_area));
         let [fp_brightness_value_area, kb_brightness_value_area] =
             Layout::vertical([Constraint::Length(1), Constraint::Length(1)])
-                .spacing(1)
+                .spacing(spacing)
                 .horizontal_margin(1)
                 .areas(values_block.inner(values_area));
 
+        self.panel.set_control_area(
+            FINGERPRINT_BRIGHTNESS_CONTROL_INDEX,
+            fp_brightness_key_area.union(fp_brightness_value_area),
+        );
+        self.panel.set_control_area(
+            KEYBOARD_BRIGHTNESS_CONTROL_INDEX,
+            kb_brightness_key_area.union(kb_brightness_value_area),
+        );
+
         // Fingerprint brightness
         self.render_fp_brightness(
             frame,
@@ -1,5 +1,5 @@
 use ratatui::{
-    crossterm::event::{Event, KeyCode, KeyEventKind},
+    crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers},
     layout::{Constraint, Layout, Rect},
     prelude::*,
     style::Styled,
@@ -9,10 +9,10 @@ use ratatui::{
 
 use crate::{
     app::AppEvent,
-    framework::info::FrameworkInfo,
+    framework::{info::FrameworkInfo, MAX_CHARGE_LIMIT_MIN},
     tui::{
         component::{AdjustableComponent, AdjustablePanel, Component},
-        control::percentage_control,
+        control::{percentage_control, AdjustableControl},
         theme::Theme,
     },
 };
@@ -20,7 +20,8 @@ use crate::{
 const NORMAL_CAPACITY_LOSS_MAX: f32 = 0.048;
 const MAX_CHARGE_LIMIT_CONTROL_INDEX: usize = 0;
 
-pub struct ChargePanelComponent(AdjustablePanel);
+/// Panel with the charge controls, and the status line of the smart charging plan
+pub struct ChargePanelComponent(AdjustablePanel, Option<String>);
 
 impl Default for ChargePanelComponent {
     fn default() -> Self {
@@ -30,11 +31,55 @@ impl Default for ChargePanelComponent {
 
 impl ChargePanelComponent {
     pub fn new() -> Self {
-        Self(AdjustablePanel {
-            selected: false,
-            controls: vec![percentage_control(0)],
-            selected_control: MAX_CHARGE_LIMIT_CONTROL_INDEX,
-        })
+        Self(
+            AdjustablePanel {
+                selected: false,
+                controls: vec![percentage_control(0)],
+                selected_control: MAX_CHARGE_LIMIT_CONTROL_INDEX,
+                control_areas: Vec::new(),
+                input: None,
+                compact: false,
+            },
+            None,
+        )
+    }
+
+    /// Updated by the app on every tick
+    pub fn set_charge_plan_status(&mut self, status: Option<String>) {
+        self.1 = status;
+    }
+
+    /// Raise a typed or adjusted max charge limit to [`MAX_CHARGE_LIMIT_MIN`]
+    fn validate_focused_max_charge_limit_control(&mut self) {
+        if self.0.selected_control != MAX_CHARGE_LIMIT_CONTROL_INDEX {
+            return;
+        }
+        if let Some(value) = self
+            .0
+            .get_selected_and_focused_control()
+            .and_then(AdjustableControl::get_percentage_value)
+        {
+            self.0.set_focused_value(value.max(MAX_CHARGE_LIMIT_MIN));
+        }
+    }
+
+    fn render_charge_plan(
+        &self,
+        frame: &mut Frame,
+        key_area: Rect,
+        value_area: Rect,
+        theme: &Theme,
+    ) {
+        if let Some(status) = &self.1 {
+            frame.render_widget(
+                Paragraph::new(self.0.label("Smart charge", "Plan")),
+                key_area,
+            );
+            frame.render_widget(
+                Paragraph::new(status.as_str()).style(Style::default().fg(theme.highlighted_text)),
+                value_area,
+            );
+        }
     }
 
     fn render_charge_level(
@@ -47,15 +92,13 @@ impl ChargePanelComponent {
     ) {
         let gauge = match info.charge_percentage {
             Some(charge_percentage) => {
-                let gauge_style = if charge_percentage < 15 {
-                    Style::default()
-                        .fg(theme.indication_warning)
-                        .bg(theme.bar_background)
+                // NOTE: themes with the same colour for both still tell them apart
+                let gauge_style = if info.is_charge_low() {
+                    theme.warning_style()
                 } else {
-                    Style::default()
-                        .fg(theme.indication_ok)
-                        .bg(theme.bar_background)
-                };
+                    theme.ok_style()
+                }
+                .bg(theme.bar_background);
                 let label = format!("{} {}%", info.charging_status, charge_percentage);
 
                 Gauge::default()
@@ -66,7 +109,10 @@ impl ChargePanelComponent {
             None => Gauge::default().percent(0).label("N/A"),
         };
 
-        frame.render_widget(Paragraph::new("Charge level"), key_area);
+        frame.render_widget(
+            Paragraph::new(self.0.label("Charge level", "Charge")),
+            key_area,
+        );
         frame.render_widget(gauge, value_area);
     }
 
@@ -127,7 +173,7 @@ impl ChargePanelComponent {
         };
 
         frame.render_widget(
-            Paragraph::new("Max charge limit").set_style(style),
+            Paragraph::new(self.0.label("Max charge limit", "Limit")).set_style(style),
             key_area,
         );
         frame.render_widget(gauge, value_area);
@@ -146,7 +192,10 @@ impl ChargePanelComponent {
             None => "N/A".to_string(),
         };
 
-        frame.render_widget(Paragraph::new("Charger voltage"), key_area);
+        frame.render_widget(
+            Paragraph::new(self.0.label("Charger voltage", "Voltage")),
+            key_area,
+        );
         frame.render_widget(
             Paragraph::new(charger_voltage_text).style(Style::default().fg(theme.informative_text)),
             value_area,
@@ -166,7 +215,10 @@ impl ChargePanelComponent {
             None => "N/A".to_string(),
         };
 
-        frame.render_widget(Paragraph::new("Charger current"), key_area);
+        frame.render_widget(
+            Paragraph::new(self.0.label("Charger current", "Current")),
+            key_area,
+        );
         frame.render_widget(
             Paragraph::new(charger_current_text).style(Style::default().fg(theme.informative_text)),
             value_area,
@@ -186,7 +238,10 @@ impl ChargePanelComponent {
             None => "N/A".to_string(),
         };
 
-        frame.render_widget(Paragraph::new("Design capacity"), key_area);
+        frame.render_widget(
+            Paragraph::new(self.0.label("Design capacity", "Design")),
+            key_area,
+        );
         frame.render_widget(
             Paragraph::new(design_capacity_text).style(Style::default().fg(theme.informative_text)),
             value_area,
@@ -206,7 +261,10 @@ impl ChargePanelComponent {
             None => "N/A".to_string(),
         };
 
-        frame.render_widget(Paragraph::new("Last full capacity"), key_area);
+        frame.render_widget(
+            Paragraph::new(self.0.label("Last full capacity", "Full")),
+            key_area,
+        );
         frame.render_widget(
             Paragraph::new(last_full_charge_capacity_text)
                 .style(Style::default().fg(theme.informative_text)),
@@ -231,7 +289,10 @@ impl ChargePanelComponent {
             _ => "N/A".to_string(),
         };
 
-        frame.render_widget(Paragraph::new("Capacity loss"), key_area);
+        frame.render_widget(
+            Paragraph::new(self.0.label("Capacity loss", "Loss")),
+            key_area,
+        );
         frame.render_widget(
             Paragraph::new(capacity_loss_text).style(Style::default().fg(theme.informative_text)),
             value_area,
@@ -251,7 +312,10 @@ impl ChargePanelComponent {
             None => "N/A".to_string(),
         };
 
-        frame.render_widget(Paragraph::new("Cycle count"), key_area);
+        frame.render_widget(
+            Paragraph::new(self.0.label("Cycle count", "Cycles")),
+            key_area,
+        );
         frame.render_widget(
             Paragraph::new(cycle_count_text).style(Style::default().fg(theme.informative_text)),
             value_area,
@@ -271,9 +335,9 @@ impl ChargePanelComponent {
         let capacity_loss_per_cycle_style = match capacity_loss_per_cycle {
             Some(capacity_loss_per_cycle) => {
                 if capacity_loss_per_cycle < NORMAL_CAPACITY_LOSS_MAX {
-                    Style::default().fg(theme.indication_ok)
+                    theme.ok_style()
                 } else {
-                    Style::default().fg(theme.indication_warning)
+                    theme.warning_style()
                 }
             }
             None => Style::default(),
@@ -291,7 +355,10 @@ impl ChargePanelComponent {
             _ => "N/A".to_string(),
         };
 
-        frame.render_widget(Paragraph::new("Capacity loss per cycle"), key_area);
+        frame.render_widget(
+            Paragraph::new(self.0.label("Capacity loss per cycle", "Loss/cycle")),
+            key_area,
+        );
         frame.render_widget(
             Paragraph::new(capacity_loss_per_cycle_text).style(capacity_loss_per_cycle_style),
             value_area,
@@ -308,6 +375,10 @@ impl AdjustableComponent for ChargePanelComponent {
 impl Component for ChargePanelComponent {
     fn handle_input(&mut self, event: Event) -> Option<crate::app::AppEvent> {
         let mut app_event = None;
+        let event = match event {
+            Event::Mouse(mouse) => self.0.mouse_to_key(mouse)?,
+            event => event,
+        };
 
         if self.0.is_selected() {
             if let Event::Key(key) = event {
@@ -315,7 +386,11 @@ impl Component for ChargePanelComponent {
                     match key.code {
                         KeyCode::Down => self.0.cycle_controls_down(),
                         KeyCode::Up => self.0.cycle_controls_up(),
+                        // NOTE: nothing to apply until a digit is typed again
+                        KeyCode::Enter if self.0.is_input_erased() => {}
                         KeyCode::Enter => {
+                            self.validate_focused_max_charge_limit_control();
+
                             match self.0.get_selected_and_focused_control() {
                                 Some(control)
                                     if self.0.selected_control
@@ -330,9 +405,22 @@ impl Component for ChargePanelComponent {
 
                             self.0.toggle_selected_control_focus()
                         }
+                        KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                            self.0.adjust_focused_percentage_control_by_delta(-1)
+                        }
+                        KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                            self.0.adjust_focused_percentage_control_by_delta(1)
+                        }
                         KeyCode::Left => self.0.adjust_focused_percentage_control_by_delta(-5),
                         KeyCode::Right => self.0.adjust_focused_percentage_control_by_delta(5),
+                        KeyCode::PageDown => self.0.adjust_focused_percentage_control_by_delta(-10),
+                        KeyCode::PageUp => self.0.adjust_focused_percentage_control_by_delta(10),
+                        KeyCode::Char(c @ '0'..='9') => self.0.enter_digit(c),
+                        KeyCode::Backspace => self.0.erase_digit(),
                         KeyCode::Esc => self.0.toggle_selected_control_focus(),
+                        KeyCode::Char('f') if !self.0.get_selected_control().is_focused() => {
+                            app_event = Some(AppEvent::ToggleFullCharge)
+                        }
                         _ => {}
                     }
                 }
@@ -352,16 +440,18 @@ impl Component for ChargePanelComponent {
         let [keys_area, values_area] =
             Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
                 .horizontal_margin(2)
-                .vertical_margin(1)
+                .vertical_margin(if self.0.compact { 0 } else { 1 })
                 .areas(block.inner(area));
+        // NOTE: the compact layout has no room for the gap below the charge level
+        let gap = if self.0.compact { 0 } else { 1 };
 
         let keys_block = Block::default().borders(Borders::NONE);
         let values_block = Block::default().borders(Borders::NONE);
 
-        let [charge_level_key_area, _empty1_key_area, charge_limit_key_area, _empty2_key_area, charger_voltage_key_area, charger_current_key_area, design_capacity_key_area, last_full_capacity_key_area, capacity_loss_key_area, cycle_count_key_area, capacity_loss_per_cycle_key_area] =
+        let [charge_level_key_area, _empty1_key_area, charge_limit_key_area, charge_plan_key_area, charger_voltage_key_area, charger_current_key_area, design_capacity_key_area, last_full_capacity_key_area, capacity_loss_key_area, cycle_count_key_area, capacity_loss_per_cycle_key_area] =
             Layout::vertical([
                 Constraint::Length(1),
-                Constraint::Length(1),
+                Constraint::Length(gap),
                 Constraint::Length(1),
                 Constraint::Length(1),
                 Constraint::Length(1),
@@ -373,10 +463,10 @@ impl Component for ChargePanelComponent {
                 Constraint::Length(1),
             ])
             .areas(keys_block.inner(keys_area));
-        let [charge_level_value_area, _empty1_value_area, charge_limit_value_area, _empty2_value_area, charger_voltage_value_area, charger_current_value_area, design_capacity_value_area, last_full_capacity_value_area, capacity_loss_value_area, cycle_count_value_area, capacity_loss_per_cycle_value_area] =
+        let [charge_level_value_area, _empty1_value_area, charge_limit_value_area, charge_plan_value_area, charger_voltage_value_area, charger_current_value_area, design_capacity_value_area, last_full_capacity_value_area, capacity_loss_value_area, cycle_count_value_area, capacity_loss_per_cycle_value_area] =
             Layout::vertical([
                 Constraint::Length(1),
-                Constraint::Length(1),
+                Constraint::Length(gap),
                 Constraint::Length(1),
                 Constraint::Length(1),
                 Constraint::Length(1),
@@ -399,6 +489,11 @@ impl Component for ChargePanelComponent {
             info,
         );
 
+        self.0.set_control_area(
+            MAX_CHARGE_LIMIT_CONTROL_INDEX,
+            charge_limit_key_area.union(charge_limit_value_area),
+        );
+
         // Max charge limit
         self.render_max_charge_limit(
             frame,
@@ -408,6 +503,9 @@ impl Component for ChargePanelComponent {
             info,
         );
 
+        // Smart charging plan
+        self.render_charge_plan(frame, charge_plan_key_area, charge_plan_value_area, theme);
+
         // Charger voltage
         self.render_charger_voltage(
             frame,
@@ -481,14 +579,22 @@ impl Component for ChargePanelComponent {
 
 #[cfg(test)]
 mod tests {
-    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent};
+    use ratatui::{
+        crossterm::event::{
+            Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+        },
+        layout::Rect,
+    };
 
-    use crate::tui::{
-        component::{
-            charge_panel::{ChargePanelComponent, MAX_CHARGE_LIMIT_CONTROL_INDEX},
-            Component,
+    use crate::{
+        app::AppEvent,
+        tui::{
+            component::{
+                charge_panel::{ChargePanelComponent, MAX_CHARGE_LIMIT_CONTROL_INDEX},
+                Component,
+            },
+            control::AdjustableControl,
         },
-        control::AdjustableControl,
     };
 
     #[test]
@@ -523,4 +629,81 @@ mod tests {
             AdjustableControl::Percentage(true, 0)
         ));
     }
+
+    #[test]
+    fn click_focuses_the_control_and_wheel_adjusts_it() {
+        let mut panel = ChargePanelComponent::new();
+        let mouse = |kind, column| {
+            Event::Mouse(MouseEvent {
+                kind,
+                column,
+                row: 3,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+
+        panel.0.toggle();
+        panel
+            .0
+            .set_control_area(MAX_CHARGE_LIMIT_CONTROL_INDEX, Rect::new(2, 3, 40, 1));
+        let _ = panel.handle_input(mouse(MouseEventKind::Down(MouseButton::Left), 50));
+        assert!(!panel.0.get_selected_control().is_focused());
+
+        let _ = panel.handle_input(mouse(MouseEventKind::Down(MouseButton::Left), 10));
+        let _ = panel.handle_input(mouse(MouseEventKind::ScrollUp, 10));
+        assert!(matches!(
+            panel.0.get_selected_control(),
+            AdjustableControl::Percentage(true, 5)
+        ));
+
+        let event = panel.handle_input(mouse(MouseEventKind::Down(MouseButton::Left), 10));
+        assert_eq!(event, Some(AppEvent::SetMaxChargeLimit(25)));
+        assert!(!panel.0.get_selected_control().is_focused());
+    }
+
+    #[test]
+    fn typed_digits_are_capped_and_applied() {
+        let mut panel = ChargePanelComponent::new();
+        let key = |code, modifiers| Event::Key(KeyEvent::new(code, modifiers));
+
+        panel.0.toggle();
+        panel.0.toggle_selected_control_focus();
+        for c in "1234".chars() {
+            let _ = panel.handle_input(key(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        assert!(matches!(
+            panel.0.get_selected_control(),
+            AdjustableControl::Percentage(true, 100)
+        ));
+
+        let _ = panel.handle_input(key(KeyCode::Backspace, KeyModifiers::NONE));
+        let _ = panel.handle_input(key(KeyCode::Backspace, KeyModifiers::NONE));
+        let _ = panel.handle_input(key(KeyCode::Char('7'), KeyModifiers::NONE));
+        let _ = panel.handle_input(key(KeyCode::Right, KeyModifiers::SHIFT));
+        let _ = panel.handle_input(key(KeyCode::PageDown, KeyModifiers::NONE));
+
+        let event = panel.handle_input(key(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(event, Some(AppEvent::SetMaxChargeLimit(25)));
+    }
+
+    #[test]
+    fn enter_is_ignored_once_the_typed_value_is_erased() {
+        let mut panel = ChargePanelComponent::new();
+        let key = |code| Event::Key(KeyEvent::from(code));
+
+        panel.0.toggle();
+        panel.0.toggle_selected_control_focus();
+        let _ = panel.handle_input(key(KeyCode::Char('8')));
+        let _ = panel.handle_input(key(KeyCode::Backspace));
+
+        assert_eq!(panel.handle_input(key(KeyCode::Enter)), None);
+        assert!(panel.0.get_selected_control().is_focused());
+
+        let _ = panel.handle_input(key(KeyCode::Char('8')));
+        let _ = panel.handle_input(key(KeyCode::Char('5')));
+        assert_eq!(
+            panel.handle_input(key(KeyCode::Enter)),
+            Some(AppEvent::SetMaxChargeLimit(85))
+        );
+    }
 }
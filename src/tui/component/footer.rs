@@ -6,12 +6,25 @@ use ratatui::{
 };
 
 use crate::{
-    app::{FOOTER_HELP, VERSION},
+    app::VERSION,
     framework::info::FrameworkInfo,
     tui::{component::Component, theme::Theme},
 };
 
-pub struct FooterComponent;
+/// Help line generated from the active keymap, and the version
+pub struct FooterComponent {
+    help: String,
+}
+
+impl FooterComponent {
+    pub fn new(help: String) -> Self {
+        Self { help }
+    }
+
+    pub fn set_help(&mut self, help: String) {
+        self.help = help;
+    }
+}
 
 impl Component for FooterComponent {
     fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme, _info: &FrameworkInfo) {
@@ -27,7 +40,7 @@ impl Component for FooterComponent {
                 .areas(block.inner(area));
 
         frame.render_widget(
-            Paragraph::new(FOOTER_HELP).style(Style::default().fg(theme.indication_ok)),
+            Paragraph::new(self.help.as_str()).style(Style::default().fg(theme.indication_ok)),
             help_area,
         );
         frame.render_widget(
@@ -0,0 +1,273 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::AppEvent,
+    framework::info::FrameworkInfo,
+    tui::{
+        component::Component,
+        keymap::{Action, KeyBinding, Keymap},
+        theme::Theme,
+    },
+};
+
+/// Where the keys are pressed, it decides which of them do something
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelpContext {
+    Dashboard,
+    ChargePanel { focused: bool },
+    BrightnessPanel { focused: bool },
+}
+
+const GLOBAL: [(&[Action], &str); 8] = [
+    (&[Action::SwitchPanel], "Select the next panel"),
+    (
+        &[Action::PreviousTheme, Action::NextTheme],
+        "Previous/next theme",
+    ),
+    (&[Action::ThemePicker], "Pick a theme"),
+    (
+        &[Action::DecreaseTickInterval, Action::IncreaseTickInterval],
+        "Refresh more/less often",
+    ),
+    (&[Action::Profiles], "Profiles"),
+    (&[Action::Rules], "Rules"),
+    (&[Action::Help], "Show or hide this help"),
+    (&[Action::Quit], "Quit"),
+];
+
+const PANEL: [(&[Action], &str); 2] = [
+    (&[Action::Up, Action::Down], "Select a control"),
+    (&[Action::Confirm], "Edit the selected control"),
+];
+
+const FOCUSED: [(&[Action], &str); 5] = [
+    (
+        &[Action::Left, Action::Right],
+        "Decrease/increase the value",
+    ),
+    (
+        &[Action::FineDecrease, Action::FineIncrease],
+        "Decrease/increase by 1",
+    ),
+    (
+        &[Action::LargeDecrease, Action::LargeIncrease],
+        "Decrease/increase by 10",
+    ),
+    (&[Action::Confirm], "Apply the value"),
+    (&[Action::Cancel], "Stop editing"),
+];
+
+/// Sections of the help, every entry with its keys and what they do
+pub fn sections(
+    keymap: &Keymap,
+    context: HelpContext,
+) -> Vec<(&'static str, Vec<(String, &'static str)>)> {
+    let entries = |list: &[(&[Action], &'static str)]| {
+        list.iter()
+            .filter_map(|(actions, description)| {
+                let keys = actions
+                    .iter()
+                    .map(|action| match keymap.keys(*action) {
+                        [] => None,
+                        keys => Some(
+                            keys.iter()
+                                .map(KeyBinding::to_string)
+                                .collect::<Vec<String>>()
+                                .join(", "),
+                        ),
+                    })
+                    .collect::<Option<Vec<String>>>()?;
+
+                Some((keys.join(" / "), *description))
+            })
+            .collect::<Vec<(String, &'static str)>>()
+    };
+
+    let editing = matches!(
+        context,
+        HelpContext::ChargePanel { focused: true } | HelpContext::BrightnessPanel { focused: true }
+    );
+    let mut global = entries(&GLOBAL);
+    // NOTE: the profile numbers aren't in the keymap, they follow the profiles entry.
+    // While a control is edited the digits type its value instead.
+    if !editing {
+        let at = global.len().min(5);
+        global.insert(at, ("1-9".to_string(), "Apply a profile by its number"));
+    }
+    let mut sections = vec![("Global", global)];
+
+    match context {
+        HelpContext::Dashboard => {}
+        HelpContext::ChargePanel { focused: false } => {
+            let mut panel = entries(&PANEL);
+            panel.extend(entries(&[(
+                &[Action::ToggleFullCharge],
+                "Charge to 100% once, or cancel it",
+            )]));
+            sections.push(("Charge panel", panel));
+        }
+        HelpContext::BrightnessPanel { focused: false } => {
+            sections.push(("Brightness panel", entries(&PANEL)));
+        }
+        HelpContext::ChargePanel { focused: true }
+        | HelpContext::BrightnessPanel { focused: true } => {
+            let mut focused = entries(&FOCUSED);
+            // NOTE: typing isn't in the keymap either, it goes before applying
+            let at = focused.len().min(3);
+            focused.insert(at, ("0-9, Backspace".to_string(), "Type the value"));
+            sections.push(("Editing a control", focused));
+        }
+    }
+
+    sections
+}
+
+/// Popup listing the keys that work in the current context
+pub struct HelpComponent {
+    visible: bool,
+    sections: Vec<(&'static str, Vec<(String, &'static str)>)>,
+    scroll: u16,
+}
+
+impl HelpComponent {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            sections: Vec::new(),
+            scroll: 0,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn open(&mut self, keymap: &Keymap, context: HelpContext) {
+        self.visible = true;
+        self.sections = sections(keymap, context);
+        self.scroll = 0;
+    }
+}
+
+impl Default for HelpComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Component for HelpComponent {
+    fn handle_input(&mut self, event: Event) -> Option<AppEvent> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Esc | KeyCode::Char('?') => self.visible = false,
+                KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+                _ => {}
+            },
+            _ => {}
+        }
+
+        None
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme, _info: &FrameworkInfo) {
+        let keys_width = self
+            .sections
+            .iter()
+            .flat_map(|(_, entries)| entries.iter().map(|(keys, _)| keys.chars().count()))
+            .max()
+            .unwrap_or_default();
+
+        let mut lines = Vec::new();
+        for (title, entries) in &self.sections {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::styled(
+                format!(" {}", title),
+                Style::default()
+                    .fg(theme.highlighted_text)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            for (keys, description) in entries {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("   {:<width$}  ", keys, width = keys_width),
+                        Style::default().fg(theme.informative_text),
+                    ),
+                    Span::raw(*description),
+                ]));
+            }
+        }
+
+        let height = lines.len() as u16 + 2;
+        let max_scroll = height.saturating_sub(area.height);
+        self.scroll = self.scroll.min(max_scroll);
+
+        let [area] = Layout::vertical([Constraint::Max(height)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Max(64)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let block = Block::default()
+            .title(" Keys ")
+            .title_bottom(" [Esc] Close ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border_active))
+            .border_type(BorderType::Rounded)
+            .style(Style::default().bg(theme.background).fg(theme.text));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(block).scroll((self.scroll, 0)),
+            area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tui::{
+        component::help::{sections, HelpContext},
+        keymap::Keymap,
+    };
+
+    #[test]
+    fn help_lists_the_keys_of_the_context() {
+        let keymap = Keymap::default();
+
+        let dashboard = sections(&keymap, HelpContext::Dashboard);
+        assert_eq!(dashboard.len(), 1);
+        assert!(dashboard[0]
+            .1
+            .contains(&("b / n".to_string(), "Previous/next theme")));
+        assert!(dashboard[0]
+            .1
+            .contains(&("- / +, =".to_string(), "Refresh more/less often")));
+
+        let charge = sections(&keymap, HelpContext::ChargePanel { focused: false });
+        assert_eq!(charge[1].0, "Charge panel");
+        assert!(charge[1]
+            .1
+            .contains(&("f".to_string(), "Charge to 100% once, or cancel it")));
+
+        let editing = sections(&keymap, HelpContext::BrightnessPanel { focused: true });
+        assert_eq!(editing[1].0, "Editing a control");
+        assert!(editing[1].1.contains(&("Esc".to_string(), "Stop editing")));
+        assert!(editing[1].1.contains(&(
+            "Shift+Left / Shift+Right".to_string(),
+            "Decrease/increase by 1"
+        )));
+        assert!(!editing[0].1.iter().any(|(keys, _)| keys == "1-9"));
+        assert!(dashboard[0].1.iter().any(|(keys, _)| keys == "1-9"));
+    }
+}
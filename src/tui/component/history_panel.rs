@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    widgets::{Block, BorderType, Borders, Paragraph, Sparkline},
+    Frame,
+};
+
+use crate::{
+    framework::info::FrameworkInfo,
+    tui::{component::Component, theme::Theme},
+};
+
+/// Samples kept per chart, one per tick, more than the widest chart shows
+const HISTORY_LEN: usize = 300;
+
+/// Charge level and battery power over the last ticks, shown by the wide layout
+pub struct HistoryPanelComponent {
+    charge: VecDeque<u64>,
+    /// In tenths of a watt, sparklines only take integers
+    power: VecDeque<u64>,
+}
+
+impl HistoryPanelComponent {
+    pub fn new() -> Self {
+        Self {
+            charge: VecDeque::with_capacity(HISTORY_LEN),
+            power: VecDeque::with_capacity(HISTORY_LEN),
+        }
+    }
+
+    pub fn record(&mut self, info: &FrameworkInfo) {
+        let push = |samples: &mut VecDeque<u64>, value: u64| {
+            if samples.len() == HISTORY_LEN {
+                samples.pop_front();
+            }
+            samples.push_back(value);
+        };
+
+        push(
+            &mut self.charge,
+            info.charge_percentage.unwrap_or_default() as u64,
+        );
+        push(
+            &mut self.power,
+            (info.battery_power_watts().unwrap_or_default() * 10.0).round() as u64,
+        );
+    }
+
+    fn render_chart(
+        frame: &mut Frame,
+        area: Rect,
+        title: String,
+        samples: &VecDeque<u64>,
+        max: Option<u64>,
+        style: Style,
+    ) {
+        let [title_area, chart_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+        // NOTE: the newest samples go on the right edge
+        let shown = samples
+            .iter()
+            .skip(samples.len().saturating_sub(chart_area.width as usize))
+            .copied()
+            .collect::<Vec<u64>>();
+
+        let mut sparkline = Sparkline::default().data(shown).style(style);
+        if let Some(max) = max {
+            sparkline = sparkline.max(max);
+        }
+
+        frame.render_widget(Paragraph::new(title), title_area);
+        frame.render_widget(sparkline, chart_area);
+    }
+}
+
+impl Default for HistoryPanelComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Component for HistoryPanelComponent {
+    fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme, info: &FrameworkInfo) {
+        let block = Block::default()
+            .title(" History ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border))
+            .border_type(BorderType::Rounded);
+
+        let [charge_area, power_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)])
+                .horizontal_margin(2)
+                .vertical_margin(1)
+                .spacing(1)
+                .areas(block.inner(area));
+
+        let charge_title = match info.charge_percentage {
+            Some(charge_percentage) => format!("Charge level {}%", charge_percentage),
+            None => "Charge level N/A".to_string(),
+        };
+        Self::render_chart(
+            frame,
+            charge_area,
+            charge_title,
+            &self.charge,
+            Some(100),
+            Style::default().fg(theme.charge_bar),
+        );
+
+        let power_title = match info.battery_power_watts() {
+            Some(watts) => format!("Battery power {:.1} W", watts),
+            None => "Battery power N/A".to_string(),
+        };
+        Self::render_chart(
+            frame,
+            power_area,
+            power_title,
+            &self.power,
+            None,
+            Style::default().fg(theme.informative_text),
+        );
+
+        frame.render_widget(block, area);
+    }
+}
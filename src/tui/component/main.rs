@@ -1,8 +1,8 @@
 use std::sync::Arc;
 
 use ratatui::{
-    crossterm::event::{Event, KeyCode, KeyEventKind},
-    layout::{Constraint, Layout, Rect},
+    crossterm::event::{Event, KeyCode, KeyEventKind, MouseButton, MouseEventKind},
+    layout::{Constraint, Layout, Position, Rect},
     Frame,
 };
 
@@ -11,81 +11,196 @@ use crate::{
     tui::{
         component::{
             brightness_panel::BrightnessPanelComponent, charge_panel::ChargePanelComponent,
+            help::HelpContext, history_panel::HistoryPanelComponent,
             pd_ports_panel::PdPortsPanelComponent, privacy_panel::PrivacyPanelComponent,
             smbios_panel::SmbiosPanelComponent, AdjustableComponent, Component,
         },
+        layout::{DashboardConfig, LayoutMode, PanelKind},
         theme::Theme,
     },
 };
 
 pub struct MainComponent {
+    charge_panel: ChargePanelComponent,
+    brightness_panel: Option<BrightnessPanelComponent>,
     privacy_panel: PrivacyPanelComponent,
     smbios_panel: SmbiosPanelComponent,
     pd_ports_panel: PdPortsPanelComponent,
-    adjustable_panels: Vec<Box<dyn AdjustableComponent>>,
+    history_panel: HistoryPanelComponent,
     selected_panel: Option<usize>,
+    /// Where every adjustable panel was last drawn, for the mouse
+    panel_areas: Vec<Rect>,
+    layout: LayoutMode,
+    dashboard: DashboardConfig,
 }
 
 impl MainComponent {
     pub fn new(finterprint: Arc<Fingerprint>, info: &FrameworkInfo) -> Self {
-        let mut adjustable_panels: Vec<Box<dyn AdjustableComponent>> = Vec::new();
-        let charge_panel = Box::new(ChargePanelComponent::new());
-
-        adjustable_panels.push(charge_panel);
-
-        if Self::is_brightness_supported(info) {
-            let brightness_panel = Box::new(BrightnessPanelComponent::new(finterprint));
-
-            adjustable_panels.push(brightness_panel);
-        }
+        let brightness_panel =
+            Self::is_brightness_supported(info).then(|| BrightnessPanelComponent::new(finterprint));
 
         Self {
+            charge_panel: ChargePanelComponent::new(),
+            brightness_panel,
             privacy_panel: PrivacyPanelComponent,
             smbios_panel: SmbiosPanelComponent,
             pd_ports_panel: PdPortsPanelComponent::new(),
-            adjustable_panels,
+            history_panel: HistoryPanelComponent::new(),
             selected_panel: None,
+            panel_areas: Vec::new(),
+            layout: LayoutMode::Standard,
+            dashboard: DashboardConfig::default(),
+        }
+    }
+
+    pub fn set_layout(&mut self, layout: LayoutMode) {
+        self.layout = layout;
+
+        for index in 0..self.adjustable_panel_count() {
+            self.adjustable_panel(index)
+                .panel()
+                .set_compact(layout == LayoutMode::Compact);
+        }
+    }
+
+    pub fn set_dashboard(&mut self, dashboard: DashboardConfig) {
+        self.dashboard = dashboard;
+    }
+
+    /// Add the latest values to the history charts
+    pub fn record_history(&mut self, info: &FrameworkInfo) {
+        self.history_panel.record(info);
+    }
+
+    pub fn set_charge_plan_status(&mut self, status: Option<String>) {
+        self.charge_panel.set_charge_plan_status(status);
+    }
+
+    /// The charge panel comes first, then the brightness one where supported
+    fn adjustable_panel(&mut self, index: usize) -> &mut dyn AdjustableComponent {
+        match (index, &mut self.brightness_panel) {
+            (1, Some(brightness_panel)) => brightness_panel,
+            _ => &mut self.charge_panel,
+        }
+    }
+
+    fn adjustable_panel_count(&self) -> usize {
+        1 + self.brightness_panel.is_some() as usize
+    }
+
+    /// Selected panel and whether one of its controls is being edited
+    pub fn help_context(&mut self) -> HelpContext {
+        let Some(index) = self.selected_panel else {
+            return HelpContext::Dashboard;
+        };
+        let focused = self
+            .adjustable_panel(index)
+            .panel()
+            .get_selected_control()
+            .is_focused();
+
+        match index {
+            0 => HelpContext::ChargePanel { focused },
+            _ => HelpContext::BrightnessPanel { focused },
         }
     }
 
+    pub fn is_editing(&mut self) -> bool {
+        matches!(
+            self.help_context(),
+            HelpContext::ChargePanel { focused: true }
+                | HelpContext::BrightnessPanel { focused: true }
+        )
+    }
+
     fn switch_panels(&mut self) {
-        let len = self.adjustable_panels.len();
+        let start = self
+            .selected_panel
+            .map_or(0, |selected_panel| selected_panel + 1);
+        let next = (start..self.adjustable_panel_count()).find(|index| self.is_panel_shown(*index));
 
+        self.select_panel(next);
+    }
+
+    /// Whether the adjustable panel was drawn, every panel is before the first frame
+    fn is_panel_shown(&self, index: usize) -> bool {
+        self.panel_areas
+            .get(index)
+            .is_none_or(|area| !area.is_empty())
+    }
+
+    fn select_panel(&mut self, panel: Option<usize>) {
         if let Some(selected_panel) = self.selected_panel {
-            if selected_panel < len - 1 {
-                let next = selected_panel + 1;
+            self.adjustable_panel(selected_panel).panel().toggle();
+        }
+        if let Some(panel) = panel {
+            self.adjustable_panel(panel).panel().toggle();
+        }
 
-                self.adjustable_panels[selected_panel].panel().toggle();
-                self.adjustable_panels[next].panel().toggle();
+        self.selected_panel = panel;
+    }
+
+    /// Panels with nothing to show on this machine collapse
+    fn is_applicable(kind: PanelKind, info: &FrameworkInfo) -> bool {
+        match kind {
+            PanelKind::Charge | PanelKind::Privacy => true,
+            PanelKind::Brightness => Self::is_brightness_supported(info),
+            PanelKind::Smbios => {
+                info.smbios_version.is_some()
+                    || info.smbios_release_date.is_some()
+                    || info.smbios_vendor.is_some()
+            }
+            PanelKind::PdPorts => {
+                let ports = &info.pd_ports;
 
-                self.selected_panel = Some(next);
-            } else {
-                self.adjustable_panels[selected_panel].panel().toggle();
-                self.selected_panel = None;
+                ports.left_back.is_some()
+                    || ports.left_front.is_some()
+                    || ports.right_back.is_some()
+                    || ports.right_front.is_some()
             }
-        } else {
-            self.adjustable_panels[0].panel().toggle();
-            self.selected_panel = Some(0);
         }
     }
 
-    fn render_privacy_and_smbios_panels(
+    fn render_dashboard(
         &mut self,
         frame: &mut Frame,
         area: Rect,
         theme: &Theme,
         info: &FrameworkInfo,
     ) {
-        let [privacy_panel_area, smbios_panel_area] =
-            Layout::horizontal([Constraint::Min(0), Constraint::Min(0)]).areas(area);
-
-        // Privacy panel
-        self.privacy_panel
-            .render(frame, privacy_panel_area, theme, info);
+        let shown = |kind| Self::is_applicable(kind, info);
+        let placed = match self.layout {
+            LayoutMode::Compact => self.dashboard.place_compact(area, shown),
+            LayoutMode::Standard | LayoutMode::Wide => self.dashboard.place(area, shown),
+        };
+
+        self.panel_areas = vec![Rect::default(); self.adjustable_panel_count()];
+
+        for (kind, area) in placed {
+            let adjustable = match kind {
+                PanelKind::Charge => Some(0),
+                PanelKind::Brightness => Some(1),
+                _ => None,
+            };
+
+            match (kind, adjustable) {
+                (_, Some(index)) => {
+                    self.panel_areas[index] = area;
+                    self.adjustable_panel(index)
+                        .render(frame, area, theme, info);
+                }
+                (PanelKind::Privacy, _) => self.privacy_panel.render(frame, area, theme, info),
+                (PanelKind::Smbios, _) => self.smbios_panel.render(frame, area, theme, info),
+                (_, None) => self.pd_ports_panel.render(frame, area, theme, info),
+            }
+        }
 
-        // SMBIOS panel
-        self.smbios_panel
-            .render(frame, smbios_panel_area, theme, info);
+        // NOTE: a panel that is no longer drawn can't keep the keys
+        if let Some(selected_panel) = self.selected_panel {
+            if !self.is_panel_shown(selected_panel) {
+                self.select_panel(None);
+            }
+        }
     }
 
     fn is_brightness_supported(info: &FrameworkInfo) -> bool {
@@ -96,48 +211,45 @@ impl MainComponent {
 
 impl Component for MainComponent {
     fn handle_input(&mut self, event: Event) -> Option<crate::app::AppEvent> {
+        // A click selects the panel under it, the panel handles the rest
+        if let Event::Mouse(mouse) = &event {
+            let position = Position::new(mouse.column, mouse.row);
+            let panel = self
+                .panel_areas
+                .iter()
+                .position(|area| area.contains(position))?;
+
+            if mouse.kind == MouseEventKind::Down(MouseButton::Left)
+                && self.selected_panel != Some(panel)
+            {
+                self.select_panel(Some(panel));
+            }
+
+            return self.adjustable_panel(panel).handle_input(event);
+        }
+
         if let Event::Key(key) = &event {
             if key.kind == KeyEventKind::Press && key.code == KeyCode::Tab {
                 self.switch_panels();
             }
         }
 
-        self.adjustable_panels
-            .iter_mut()
-            .find_map(|panel| panel.handle_input(event.clone()))
+        (0..self.adjustable_panel_count())
+            .find_map(|index| self.adjustable_panel(index).handle_input(event.clone()))
     }
 
     fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme, info: &FrameworkInfo) {
-        let [top_area, pd_ports_panel_area] =
-            Layout::vertical([Constraint::Max(15), Constraint::Min(0)]).areas(area);
-        let [charge_panel_area, top_right_area] =
-            Layout::horizontal([Constraint::Min(0), Constraint::Min(0)]).areas(top_area);
-
-        // Charge panel
-        self.adjustable_panels[0].render(frame, charge_panel_area, theme, info);
-
-        // Show brightness panel only on supported platforms
-        if Self::is_brightness_supported(info) {
-            let [brightness_panel_area, privacy_and_smbios_panels_area] =
-                Layout::vertical([Constraint::Min(7), Constraint::Min(7)]).areas(top_right_area);
-
-            // Brightness panel (top of right_area)
-            self.adjustable_panels[1].render(frame, brightness_panel_area, theme, info);
-
-            // Privacy and SMBIOS panels
-            self.render_privacy_and_smbios_panels(
-                frame,
-                privacy_and_smbios_panels_area,
-                theme,
-                info,
-            );
-        } else {
-            // Privacy and SMBIOS panels
-            self.render_privacy_and_smbios_panels(frame, top_right_area, theme, info);
-        }
+        match self.layout {
+            LayoutMode::Compact | LayoutMode::Standard => {
+                self.render_dashboard(frame, area, theme, info)
+            }
+            LayoutMode::Wide => {
+                let [dashboard_area, history_area] =
+                    Layout::horizontal([Constraint::Max(140), Constraint::Min(0)]).areas(area);
 
-        // PD Ports panel (bottom of right_area)
-        self.pd_ports_panel
-            .render(frame, pd_ports_panel_area, theme, info);
+                self.render_dashboard(frame, dashboard_area, theme, info);
+                self.history_panel.render(frame, history_area, theme, info);
+            }
+        }
     }
 }
@@ -22,9 +22,9 @@ impl PrivacyPanelComponent {
         info: &FrameworkInfo,
     ) {
         let toggle = if info.is_microphone_enabled {
-            Paragraph::new("ON").style(Style::default().fg(theme.indication_ok))
+            Paragraph::new("ON").style(theme.ok_style())
         } else {
-            Paragraph::new("OFF").style(Style::default().fg(theme.indication_warning))
+            Paragraph::new("OFF").style(theme.warning_style())
         };
 
         frame.render_widget(Paragraph::new("Microphone"), key_area);
@@ -40,9 +40,9 @@ impl PrivacyPanelComponent {
         info: &FrameworkInfo,
     ) {
         let toggle = if info.is_camera_enabled {
-            Paragraph::new("ON").style(Style::default().fg(theme.indication_ok))
+            Paragraph::new("ON").style(theme.ok_style())
         } else {
-            Paragraph::new("OFF").style(Style::default().fg(theme.indication_warning))
+            Paragraph::new("OFF").style(theme.warning_style())
         };
 
         frame.render_widget(Paragraph::new("Camera"), key_area);
@@ -0,0 +1,143 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::{
+    app::AppEvent,
+    framework::info::FrameworkInfo,
+    tui::{component::Component, theme::Theme},
+};
+
+/// Popup listing the profiles from the config, in the order of the number keys
+pub struct ProfilePickerComponent {
+    visible: bool,
+    names: Vec<String>,
+    active: Option<String>,
+    state: ListState,
+}
+
+impl ProfilePickerComponent {
+    pub fn new(names: Vec<String>) -> Self {
+        Self {
+            visible: false,
+            names,
+            active: None,
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn set_names(&mut self, names: Vec<String>) {
+        self.names = names;
+        self.state.select(Some(0));
+    }
+
+    pub fn set_active(&mut self, active: Option<String>) {
+        self.active = active;
+    }
+
+    /// Profile bound to the number key `n`, starting at 1
+    pub fn by_number(&self, n: usize) -> Option<AppEvent> {
+        let name = self.names.get(n.checked_sub(1)?)?;
+
+        Some(AppEvent::ApplyProfile(name.clone()))
+    }
+}
+
+impl Component for ProfilePickerComponent {
+    fn handle_input(&mut self, event: Event) -> Option<AppEvent> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Esc | KeyCode::Char('p') => {
+                    self.visible = false;
+                    None
+                }
+                KeyCode::Up => {
+                    self.state.select_previous();
+                    None
+                }
+                KeyCode::Down => {
+                    self.state.select_next();
+                    None
+                }
+                KeyCode::Enter => {
+                    self.visible = false;
+                    self.by_number(self.state.selected()? + 1)
+                }
+                KeyCode::Char(c) => {
+                    let event = self.by_number(c.to_digit(10)? as usize);
+                    self.visible = event.is_none();
+                    event
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme, _info: &FrameworkInfo) {
+        let height = (self.names.len() as u16).max(1) + 2;
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Max(40)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let block = Block::default()
+            .title(" Profiles ")
+            .title_bottom(" [Enter] Apply [Esc] Close ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border_active))
+            .border_type(BorderType::Rounded)
+            .style(Style::default().bg(theme.background).fg(theme.text));
+
+        let items: Vec<ListItem> = if self.names.is_empty() {
+            vec![ListItem::new(" No profiles in the config")]
+        } else {
+            self.names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| {
+                    let number = if index < 9 {
+                        format!(" {} ", index + 1)
+                    } else {
+                        "   ".to_string()
+                    };
+                    let active = if self.active.as_ref() == Some(name) {
+                        " (active)"
+                    } else {
+                        ""
+                    };
+
+                    ListItem::new(Line::from(vec![
+                        Span::styled(number, Style::default().fg(theme.highlighted_text)),
+                        Span::raw(name.as_str()),
+                        Span::styled(active, theme.ok_style()),
+                    ]))
+                })
+                .collect()
+        };
+
+        let list = List::new(items).block(block).highlight_style(
+            Style::default()
+                .bg(theme.border_active)
+                .fg(theme.background),
+        );
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.state);
+    }
+}
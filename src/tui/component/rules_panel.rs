@@ -0,0 +1,129 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::{
+    app::AppEvent,
+    framework::info::FrameworkInfo,
+    rules::RuleStatus,
+    tui::{component::Component, theme::Theme},
+};
+
+/// Popup listing the automation rules from the config
+pub struct RulesPanelComponent {
+    visible: bool,
+    statuses: Vec<RuleStatus>,
+    state: ListState,
+}
+
+impl RulesPanelComponent {
+    pub fn new(statuses: Vec<RuleStatus>) -> Self {
+        Self {
+            visible: false,
+            statuses,
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn set_statuses(&mut self, statuses: Vec<RuleStatus>) {
+        self.statuses = statuses;
+    }
+
+    fn toggle_selected_rule(&mut self) -> Option<AppEvent> {
+        let index = self.state.selected()?;
+        let status = self.statuses.get_mut(index)?;
+
+        status.enabled = !status.enabled;
+
+        Some(AppEvent::SetRuleEnabled(index, status.enabled))
+    }
+}
+
+impl Component for RulesPanelComponent {
+    fn handle_input(&mut self, event: Event) -> Option<AppEvent> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Esc | KeyCode::Char('r') => {
+                    self.visible = false;
+                    None
+                }
+                KeyCode::Up => {
+                    self.state.select_previous();
+                    None
+                }
+                KeyCode::Down => {
+                    self.state.select_next();
+                    None
+                }
+                KeyCode::Enter | KeyCode::Char(' ') => self.toggle_selected_rule(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme, _info: &FrameworkInfo) {
+        let height = (self.statuses.len() as u16).max(1) + 2;
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Max(70)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let block = Block::default()
+            .title(" Rules ")
+            .title_bottom(" [Enter] Enable/Disable [Esc] Close ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border_active))
+            .border_type(BorderType::Rounded)
+            .style(Style::default().bg(theme.background).fg(theme.text));
+
+        let items: Vec<ListItem> = if self.statuses.is_empty() {
+            vec![ListItem::new(" No rules in the config")]
+        } else {
+            self.statuses
+                .iter()
+                .map(|status| {
+                    let (checkbox, checkbox_style) = if status.enabled {
+                        ("[x] ", theme.ok_style())
+                    } else {
+                        ("[ ] ", theme.warning_style())
+                    };
+                    let last_fired = match status.last_fired {
+                        Some(last_fired) => last_fired.format("%Y-%m-%d %H:%M:%S").to_string(),
+                        None => "never".to_string(),
+                    };
+
+                    ListItem::new(Line::from(vec![
+                        Span::styled(checkbox, checkbox_style),
+                        Span::raw(format!("{:<40}", status.name)),
+                        Span::styled(last_fired, Style::default().fg(theme.highlighted_text)),
+                    ]))
+                })
+                .collect()
+        };
+
+        let list = List::new(items).block(block).highlight_style(
+            Style::default()
+                .bg(theme.border_active)
+                .fg(theme.background),
+        );
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.state);
+    }
+}
@@ -0,0 +1,258 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::{
+    framework::info::FrameworkInfo,
+    tui::{
+        component::Component,
+        theme::{Theme, ThemeId},
+    },
+};
+
+/// What the picker asks the TUI to do with the themes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemePick {
+    /// Show the theme behind the popup without saving it
+    Preview(ThemeId),
+    Commit(ThemeId),
+    /// Go back to the theme the picker was opened with
+    Revert(ThemeId),
+}
+
+/// Popup listing every theme with its colours, typing filters the list by name
+pub struct ThemePickerComponent {
+    visible: bool,
+    themes: Vec<Theme>,
+    filter: String,
+    original: Option<ThemeId>,
+    state: ListState,
+}
+
+impl ThemePickerComponent {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            themes: Vec::new(),
+            filter: String::new(),
+            original: None,
+            state: ListState::default(),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Show the popup with `themes` in cycle order and `current` selected
+    pub fn open(&mut self, themes: Vec<Theme>, current: ThemeId) {
+        self.visible = true;
+        self.filter.clear();
+        self.original = Some(current);
+        self.state
+            .select(themes.iter().position(|theme| theme.id == current));
+        self.themes = themes;
+    }
+
+    fn matching(&self) -> Vec<&Theme> {
+        let filter = self.filter.to_lowercase();
+
+        self.themes
+            .iter()
+            .filter(|theme| theme.id.name().to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    fn selected(&self) -> Option<ThemeId> {
+        self.matching()
+            .get(self.state.selected()?)
+            .map(|theme| theme.id)
+    }
+
+    fn close(&mut self) -> Option<ThemeId> {
+        self.visible = false;
+        self.original.take()
+    }
+
+    pub fn handle_key(&mut self, event: Event) -> Option<ThemePick> {
+        let Event::Key(key) = event else {
+            return None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.close().map(ThemePick::Revert),
+            KeyCode::Enter => match self.selected() {
+                Some(id) => {
+                    self.close();
+                    Some(ThemePick::Commit(id))
+                }
+                None => self.close().map(ThemePick::Revert),
+            },
+            KeyCode::Up => {
+                self.state.select(Some(
+                    self.state.selected().map_or(0, |i| i.saturating_sub(1)),
+                ));
+                self.selected().map(ThemePick::Preview)
+            }
+            KeyCode::Down => {
+                // NOTE: ListState only clamps the selection when rendering
+                let last = self.matching().len().saturating_sub(1);
+                self.state
+                    .select(Some(self.state.selected().map_or(0, |i| (i + 1).min(last))));
+                self.selected().map(ThemePick::Preview)
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.state.select(Some(0));
+                self.selected().map(ThemePick::Preview)
+            }
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.state.select(Some(0));
+                self.selected().map(ThemePick::Preview)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for ThemePickerComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Component for ThemePickerComponent {
+    fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme, _info: &FrameworkInfo) {
+        let matching = self.matching();
+        let height = (matching.len() as u16).max(1) + 2;
+        let [area] = Layout::vertical([Constraint::Max(height)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Max(44)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let title = if self.filter.is_empty() {
+            " Themes ".to_string()
+        } else {
+            format!(" Themes: {} ", self.filter)
+        };
+        let block = Block::default()
+            .title(title)
+            .title_bottom(" [Enter] Apply [Esc] Cancel, type to search ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border_active))
+            .border_type(BorderType::Rounded)
+            .style(Style::default().bg(theme.background).fg(theme.text));
+
+        let items: Vec<ListItem> = if matching.is_empty() {
+            vec![ListItem::new(" No matching themes")]
+        } else {
+            matching
+                .iter()
+                .map(|candidate| {
+                    let swatch = [
+                        candidate.background,
+                        candidate.text,
+                        candidate.border_active,
+                        candidate.indication_ok,
+                        candidate.indication_warning,
+                        candidate.charge_bar,
+                        candidate.highlighted_text,
+                    ]
+                    .into_iter()
+                    .map(|color| Span::styled("  ", Style::default().bg(color)));
+
+                    ListItem::new(Line::from(
+                        std::iter::once(Span::raw(format!(" {:<22}", candidate.id.name())))
+                            .chain(swatch)
+                            .collect::<Vec<Span>>(),
+                    ))
+                })
+                .collect()
+        };
+
+        let list = List::new(items).block(block).highlight_style(
+            Style::default()
+                .bg(theme.border_active)
+                .fg(theme.background),
+        );
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent};
+
+    use crate::tui::{
+        component::theme_picker::{ThemePick, ThemePickerComponent},
+        theme::{custom::Themes, ThemeVariant},
+    };
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::from(code))
+    }
+
+    fn picker() -> ThemePickerComponent {
+        let themes = Themes::default();
+        let mut picker = ThemePickerComponent::new();
+        picker.open(
+            themes
+                .ids()
+                .into_iter()
+                .filter_map(|id| themes.get(id))
+                .collect(),
+            ThemeVariant::Dracula.into(),
+        );
+
+        picker
+    }
+
+    #[test]
+    fn moving_previews_and_escape_reverts() {
+        let mut picker = picker();
+
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Down)),
+            Some(ThemePick::Preview(ThemeVariant::GameBoy.into()))
+        );
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Esc)),
+            Some(ThemePick::Revert(ThemeVariant::Dracula.into()))
+        );
+        assert!(!picker.is_visible());
+    }
+
+    #[test]
+    fn search_filters_by_name_and_enter_commits() {
+        let mut picker = picker();
+
+        for c in "gruv".chars() {
+            picker.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Down)),
+            Some(ThemePick::Preview(ThemeVariant::GruvboxLight.into()))
+        );
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Down)),
+            Some(ThemePick::Preview(ThemeVariant::GruvboxLight.into()))
+        );
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Enter)),
+            Some(ThemePick::Commit(ThemeVariant::GruvboxLight.into()))
+        );
+    }
+}
@@ -10,20 +10,28 @@ use crate::{
     framework::info::FrameworkInfo,
     tui::{
         component::Component,
-        theme::{Theme, ThemeVariant},
+        keymap::Action,
+        theme::{Theme, ThemeId},
     },
 };
 
 pub struct TitleComponent {
     theme_name: String,
     tick_interval_ms: u64,
+    profile: Option<(String, bool)>,
+    /// Where the theme and the interval were last drawn, for the mouse
+    theme_area: Rect,
+    tick_interval_area: Rect,
 }
 
 impl TitleComponent {
-    pub fn new(theme: ThemeVariant) -> Self {
+    pub fn new(theme: ThemeId) -> Self {
         Self {
             theme_name: theme.name().to_string(),
             tick_interval_ms: 1000,
+            profile: None,
+            theme_area: Rect::default(),
+            tick_interval_area: Rect::default(),
         }
     }
 
@@ -34,6 +42,32 @@ impl TitleComponent {
     pub fn set_tick_interval(&mut self, interval_ms: u64) {
         self.tick_interval_ms = interval_ms;
     }
+
+    /// Action of a click: the left half of the theme and the interval goes
+    /// back or down, the right half forward or up
+    pub fn click(&self, column: u16, row: u16) -> Option<Action> {
+        let position = Position::new(column, row);
+        let right_half = |area: Rect| column >= area.x + area.width / 2;
+
+        if self.theme_area.contains(position) {
+            Some(match right_half(self.theme_area) {
+                true => Action::NextTheme,
+                false => Action::PreviousTheme,
+            })
+        } else if self.tick_interval_area.contains(position) {
+            Some(match right_half(self.tick_interval_area) {
+                true => Action::IncreaseTickInterval,
+                false => Action::DecreaseTickInterval,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Active profile and whether the live state has drifted from it
+    pub fn set_profile(&mut self, profile: Option<(String, bool)>) {
+        self.profile = profile;
+    }
 }
 
 impl Component for TitleComponent {
@@ -44,12 +78,13 @@ impl Component for TitleComponent {
             .border_style(Style::default().fg(theme.border))
             .border_type(BorderType::Rounded);
 
-        let [smbios_version_area, charging_status_area, charge_percentage_area, max_charge_limit_area, fan_speed_area, theme_area, tick_interval_area] =
+        let [smbios_version_area, charging_status_area, charge_percentage_area, max_charge_limit_area, profile_area, fan_speed_area, theme_area, tick_interval_area] =
             Layout::horizontal([
                 Constraint::Max(10),
                 Constraint::Max(15),
                 Constraint::Max(6),
                 Constraint::Max(13),
+                Constraint::Max(20),
                 Constraint::Min(18),
                 Constraint::Max(30),
                 Constraint::Max(12),
@@ -66,12 +101,10 @@ impl Component for TitleComponent {
             );
         }
 
-        let charge_percentage = info.charge_percentage;
-        let charge_style = match charge_percentage {
-            Some(charge_percentage) if charge_percentage < 15 => {
-                Style::default().fg(theme.indication_warning)
-            }
-            _ => Style::default().fg(theme.indication_ok),
+        let charge_style = if info.is_charge_low() {
+            theme.warning_style()
+        } else {
+            theme.ok_style()
         };
 
         // Charging status
@@ -97,6 +130,20 @@ impl Component for TitleComponent {
             );
         }
 
+        // Active profile, marked when the live state no longer matches it
+        if let Some((profile, drifted)) = &self.profile {
+            let (text, style) = if *drifted {
+                (format!("[ {}* ]", profile), theme.warning_style())
+            } else {
+                (
+                    format!("[ {} ]", profile),
+                    Style::default().fg(theme.informative_text),
+                )
+            };
+
+            frame.render_widget(Paragraph::new(text).style(style), profile_area);
+        }
+
         // FAN speed
         if let Some(fan_rpm) = &info.fan_rpm {
             let text = fan_rpm
@@ -115,8 +162,11 @@ impl Component for TitleComponent {
 
         // Theme name
         if !self.theme_name.is_empty() {
+            let text = format!("【←b {} n→】", self.theme_name);
+            self.theme_area = right_aligned(theme_area, &text);
+
             frame.render_widget(
-                Paragraph::new(format!("【←b {} n→】", self.theme_name))
+                Paragraph::new(text)
                     .style(Style::default().fg(theme.highlighted_text))
                     .alignment(Alignment::Right),
                 theme_area,
@@ -124,8 +174,10 @@ impl Component for TitleComponent {
         }
 
         // Tick interval
+        let text = format!("- {:4}ms +", self.tick_interval_ms);
+        self.tick_interval_area = right_aligned(tick_interval_area, &text);
         frame.render_widget(
-            Paragraph::new(format!("- {:4}ms +", self.tick_interval_ms))
+            Paragraph::new(text)
                 .style(Style::default().fg(theme.highlighted_text))
                 .alignment(Alignment::Right),
             tick_interval_area,
@@ -134,3 +186,14 @@ impl Component for TitleComponent {
         frame.render_widget(block, area);
     }
 }
+
+/// Part of `area` covered by right-aligned `text`
+fn right_aligned(area: Rect, text: &str) -> Rect {
+    let width = (Line::from(text).width() as u16).min(area.width);
+
+    Rect {
+        x: area.right() - width,
+        width,
+        ..area
+    }
+}
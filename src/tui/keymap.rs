@@ -0,0 +1,447 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Everything a key can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    PreviousTheme,
+    NextTheme,
+    ThemePicker,
+    IncreaseTickInterval,
+    DecreaseTickInterval,
+    Rules,
+    Profiles,
+    SwitchPanel,
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Change the value of the focused control by 1
+    FineDecrease,
+    FineIncrease,
+    /// Change the value of the focused control by 10
+    LargeDecrease,
+    LargeIncrease,
+    Confirm,
+    Cancel,
+    /// Enable or disable the selected rule
+    Toggle,
+    ToggleFullCharge,
+    Help,
+}
+
+impl Action {
+    pub const ALL: [Action; 22] = [
+        Action::Quit,
+        Action::PreviousTheme,
+        Action::NextTheme,
+        Action::ThemePicker,
+        Action::IncreaseTickInterval,
+        Action::DecreaseTickInterval,
+        Action::Rules,
+        Action::Profiles,
+        Action::SwitchPanel,
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::FineDecrease,
+        Action::FineIncrease,
+        Action::LargeDecrease,
+        Action::LargeIncrease,
+        Action::Confirm,
+        Action::Cancel,
+        Action::Toggle,
+        Action::ToggleFullCharge,
+        Action::Help,
+    ];
+
+    /// Key the components handle the action by, bound keys are translated to it
+    fn canonical(&self) -> (KeyCode, KeyModifiers) {
+        let code = match self {
+            Action::Quit => KeyCode::Char('q'),
+            Action::PreviousTheme => KeyCode::Char('b'),
+            Action::NextTheme => KeyCode::Char('n'),
+            Action::ThemePicker => KeyCode::Char('t'),
+            Action::IncreaseTickInterval => KeyCode::Char('+'),
+            Action::DecreaseTickInterval => KeyCode::Char('-'),
+            Action::Rules => KeyCode::Char('r'),
+            Action::Profiles => KeyCode::Char('p'),
+            Action::SwitchPanel => KeyCode::Tab,
+            Action::Up => KeyCode::Up,
+            Action::Down => KeyCode::Down,
+            Action::Left => KeyCode::Left,
+            Action::Right => KeyCode::Right,
+            Action::FineDecrease => return (KeyCode::Left, KeyModifiers::SHIFT),
+            Action::FineIncrease => return (KeyCode::Right, KeyModifiers::SHIFT),
+            Action::LargeDecrease => KeyCode::PageDown,
+            Action::LargeIncrease => KeyCode::PageUp,
+            Action::Confirm => KeyCode::Enter,
+            Action::Cancel => KeyCode::Esc,
+            Action::Toggle => KeyCode::Char(' '),
+            Action::ToggleFullCharge => KeyCode::Char('f'),
+            Action::Help => KeyCode::Char('?'),
+        };
+
+        (code, KeyModifiers::NONE)
+    }
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::IncreaseTickInterval => &["+", "="],
+            Action::Toggle => &["space"],
+            _ => &[],
+        }
+    }
+
+    fn name(&self) -> String {
+        toml::Value::try_from(self)
+            .ok()
+            .and_then(|name| name.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+/// Key with its modifiers, written as e.g. `q`, `ctrl+c`, `shift+up` or `f5`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Shift is part of the character itself, and terminals don't agree on reporting it
+    fn normalized(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+
+        Self { code, modifiers }
+    }
+
+    fn matches(&self, key: &KeyEvent) -> bool {
+        *self == Self::normalized(key.code, key.modifiers)
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifiers, key) = if s == "+" {
+            ("", "+")
+        } else if let Some(modifiers) = s.strip_suffix("++") {
+            (modifiers, "+")
+        } else {
+            s.rsplit_once('+').unwrap_or(("", s))
+        };
+
+        let mut parsed = KeyModifiers::NONE;
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            parsed |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier \"{}\" in \"{}\"", modifier, s)),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key \"{}\"", s)),
+                },
+            },
+        };
+
+        Ok(Self::normalized(code, parsed))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Tab => f.write_str("Tab"),
+            KeyCode::BackTab => f.write_str("BackTab"),
+            KeyCode::Enter => f.write_str("Enter"),
+            KeyCode::Esc => f.write_str("Esc"),
+            KeyCode::Up => f.write_str("Up"),
+            KeyCode::Down => f.write_str("Down"),
+            KeyCode::Left => f.write_str("Left"),
+            KeyCode::Right => f.write_str("Right"),
+            KeyCode::Home => f.write_str("Home"),
+            KeyCode::End => f.write_str("End"),
+            KeyCode::PageUp => f.write_str("PageUp"),
+            KeyCode::PageDown => f.write_str("PageDown"),
+            KeyCode::Backspace => f.write_str("Backspace"),
+            KeyCode::Delete => f.write_str("Delete"),
+            KeyCode::Insert => f.write_str("Insert"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+impl Serialize for KeyBinding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyBinding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Keys of every action, actions missing from the config keep their default keys
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Keymap(BTreeMap<Action, Vec<KeyBinding>>);
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self(
+            Action::ALL
+                .into_iter()
+                .map(|action| {
+                    let keys = match action.default_keys() {
+                        [] => {
+                            let (code, modifiers) = action.canonical();
+                            vec![KeyBinding::normalized(code, modifiers)]
+                        }
+                        keys => keys.iter().filter_map(|key| key.parse().ok()).collect(),
+                    };
+
+                    (action, keys)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for Keymap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Keys {
+            One(KeyBinding),
+            Many(Vec<KeyBinding>),
+        }
+
+        let mut keymap = Keymap::default();
+        for (action, keys) in BTreeMap::<Action, Keys>::deserialize(deserializer)? {
+            let keys = match keys {
+                Keys::One(key) => vec![key],
+                Keys::Many(keys) => keys,
+            };
+            keymap.0.insert(action, keys);
+        }
+
+        Ok(keymap)
+    }
+}
+
+/// Help line in the footer, actions that share a label are shown together
+const HELP: [(&[Action], &str); 10] = [
+    (&[Action::SwitchPanel], "Switch panels"),
+    (&[Action::Up, Action::Down], "Scroll"),
+    (&[Action::Confirm], "Edit/Apply"),
+    (&[Action::Left, Action::Right], "Adjust value"),
+    (&[Action::Cancel], "Cancel"),
+    (&[Action::Profiles], "Profiles"),
+    (&[Action::Rules], "Rules"),
+    (&[Action::ThemePicker], "Themes"),
+    (&[Action::Help], "Help"),
+    (&[Action::Quit], "Quit"),
+];
+
+impl Keymap {
+    pub fn keys(&self, action: Action) -> &[KeyBinding] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.0
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|binding| binding.matches(key)))
+            .map(|(action, _)| *action)
+    }
+
+    /// Keys bound to more than one action, and digits taken by the profile numbers
+    /// and by typing values
+    pub fn check(&self) -> Result<(), String> {
+        let mut bound: Vec<(KeyBinding, Action)> = Vec::new();
+        let mut conflicts = Vec::new();
+
+        for (action, keys) in &self.0 {
+            for key in keys {
+                if let Some((_, other)) = bound.iter().find(|(other, _)| other == key) {
+                    conflicts.push(format!(
+                        "{} is bound to both {} and {}",
+                        key,
+                        other.name(),
+                        action.name()
+                    ));
+                } else if key.modifiers.is_empty() && matches!(key.code, KeyCode::Char('0'..='9')) {
+                    conflicts.push(format!(
+                        "{} is bound to {} but selects a profile or enters a value",
+                        key,
+                        action.name()
+                    ));
+                }
+                bound.push((*key, *action));
+            }
+        }
+
+        match conflicts.is_empty() {
+            true => Ok(()),
+            false => Err(format!("Conflicting keys: {}", conflicts.join(", "))),
+        }
+    }
+
+    /// Translate a bound key to the key the components handle its action by.
+    /// Unbound keys are dropped, except for digits and Backspace which select
+    /// profiles and edit typed values.
+    pub fn translate(&self, event: Event) -> Option<Event> {
+        let Event::Key(key) = event else {
+            return Some(event);
+        };
+
+        match self.action(&key) {
+            Some(action) => {
+                let (code, modifiers) = action.canonical();
+
+                Some(Event::Key(KeyEvent::new_with_kind(
+                    code, modifiers, key.kind,
+                )))
+            }
+            None if key.modifiers.is_empty()
+                && matches!(key.code, KeyCode::Char('0'..='9') | KeyCode::Backspace) =>
+            {
+                Some(event)
+            }
+            None => None,
+        }
+    }
+
+    /// Help line for the footer, with the first key of every action
+    pub fn help(&self) -> String {
+        HELP.iter()
+            .filter_map(|(actions, label)| {
+                let keys = actions
+                    .iter()
+                    .map(|action| self.keys(*action).first().map(KeyBinding::to_string))
+                    .collect::<Option<Vec<String>>>()?;
+
+                Some(format!("[{}] {}", keys.join("/"), label))
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::tui::keymap::{Action, KeyBinding, Keymap};
+
+    #[test]
+    fn keys_are_parsed_with_modifiers() {
+        let parse = |key: &str| key.parse::<KeyBinding>().unwrap();
+
+        assert_eq!(parse("q").code, KeyCode::Char('q'));
+        assert_eq!(
+            parse("ctrl+c"),
+            KeyBinding {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL
+            }
+        );
+        assert_eq!(parse("Ctrl++").code, KeyCode::Char('+'));
+        assert_eq!(parse("shift+up").modifiers, KeyModifiers::SHIFT);
+        assert_eq!(parse("F5").code, KeyCode::F(5));
+        assert_eq!(parse("space").to_string(), "Space");
+        assert!("hyper+x".parse::<KeyBinding>().is_err());
+        assert!("escape-key".parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn configured_keys_replace_the_defaults_and_are_translated() {
+        let keymap: Keymap = toml::from_str("up = [\"k\", \"up\"]\nquit = \"ctrl+q\"").unwrap();
+        let key = |code, modifiers| Event::Key(KeyEvent::new(code, modifiers));
+
+        assert!(keymap.check().is_ok());
+        assert_eq!(
+            keymap.translate(key(KeyCode::Char('k'), KeyModifiers::NONE)),
+            Some(key(KeyCode::Up, KeyModifiers::NONE))
+        );
+        assert_eq!(
+            keymap.translate(key(KeyCode::Char('q'), KeyModifiers::CONTROL)),
+            Some(key(KeyCode::Char('q'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            keymap.translate(key(KeyCode::Char('q'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(keymap.keys(Action::Down)[0].code, KeyCode::Down);
+        assert!(keymap.help().ends_with("[Ctrl+q] Quit"));
+    }
+
+    #[test]
+    fn conflicts_are_reported() {
+        let keymap: Keymap = toml::from_str("up = \"n\"\nquit = \"3\"").unwrap();
+        let error = keymap.check().unwrap_err();
+
+        assert!(error.contains("n is bound to both next_theme and up"));
+        assert!(error.contains("3 is bound to quit"));
+    }
+
+    #[test]
+    fn default_help_lists_the_default_keys() {
+        assert_eq!(
+            Keymap::default().help(),
+            "[Tab] Switch panels [Up/Down] Scroll [Enter] Edit/Apply [Left/Right] Adjust value [Esc] Cancel [p] Profiles [r] Rules [t] Themes [?] Help [q] Quit"
+        );
+    }
+}
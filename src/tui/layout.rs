@@ -0,0 +1,394 @@
+use std::collections::BTreeMap;
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use serde::{Deserialize, Serialize};
+
+/// Smallest terminal the compact layout fits in
+pub const MIN_WIDTH: u16 = 50;
+pub const MIN_HEIGHT: u16 = 18;
+
+/// Smallest terminal the dashboard fits in without abbreviating
+const STANDARD_WIDTH: u16 = 100;
+const STANDARD_HEIGHT: u16 = 32;
+
+/// Terminals this wide get the history charts next to the dashboard
+const WIDE_WIDTH: u16 = 170;
+
+/// How the panels are arranged, picked from the terminal size on every frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutMode {
+    /// One column with abbreviated labels, panels that don't fit are left out
+    Compact,
+    Standard,
+    /// The standard dashboard with the history charts on the right
+    Wide,
+}
+
+impl LayoutMode {
+    /// Layout for a terminal of `area`'s size, none when it's too small for any
+    pub fn for_area(area: Rect) -> Option<Self> {
+        match (area.width, area.height) {
+            (width, height) if width < MIN_WIDTH || height < MIN_HEIGHT => None,
+            (width, height) if width < STANDARD_WIDTH || height < STANDARD_HEIGHT => {
+                Some(LayoutMode::Compact)
+            }
+            (width, _) if width < WIDE_WIDTH => Some(LayoutMode::Standard),
+            _ => Some(LayoutMode::Wide),
+        }
+    }
+
+    /// Widest the content gets, it's centered in the rest
+    pub fn max_width(&self) -> u16 {
+        match self {
+            LayoutMode::Compact => STANDARD_WIDTH,
+            LayoutMode::Standard => 140,
+            LayoutMode::Wide => 220,
+        }
+    }
+}
+
+/// Panels of the dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PanelKind {
+    Charge,
+    Brightness,
+    Privacy,
+    Smbios,
+    PdPorts,
+}
+
+impl PanelKind {
+    /// Rows the panel needs to show all of its content
+    fn min_height(&self, compact: bool) -> u16 {
+        match (self, compact) {
+            (PanelKind::Charge, false) => 15,
+            (PanelKind::Charge, true) => 12,
+            (PanelKind::Brightness, false) => 6,
+            (PanelKind::Brightness, true) => 4,
+            (PanelKind::Privacy, _) => 6,
+            (PanelKind::Smbios, _) => 7,
+            (PanelKind::PdPorts, _) => 8,
+        }
+    }
+
+    fn default_size(&self) -> u16 {
+        match self {
+            PanelKind::PdPorts => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// A panel or a list of them. The top level list is stacked from top to bottom,
+/// lists in it put their panels side by side, lists in those stack them again and so on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Node {
+    Panel(PanelKind),
+    List(Vec<Node>),
+}
+
+impl Node {
+    /// Drop the panels that aren't shown and the lists left empty
+    fn prune(&self, shown: &impl Fn(PanelKind) -> bool) -> Option<Node> {
+        match self {
+            Node::Panel(kind) => shown(*kind).then_some(Node::Panel(*kind)),
+            Node::List(nodes) => {
+                let nodes = nodes
+                    .iter()
+                    .filter_map(|node| node.prune(shown))
+                    .collect::<Vec<Node>>();
+
+                (!nodes.is_empty()).then_some(Node::List(nodes))
+            }
+        }
+    }
+
+    fn min_height(&self, vertical: bool) -> u16 {
+        match self {
+            Node::Panel(kind) => kind.min_height(false),
+            Node::List(nodes) => {
+                let heights = nodes.iter().map(|node| node.min_height(!vertical));
+
+                if vertical {
+                    heights.sum()
+                } else {
+                    heights.max().unwrap_or_default()
+                }
+            }
+        }
+    }
+
+    /// A list is as big as its biggest panel
+    fn size(&self, sizes: &BTreeMap<PanelKind, u16>) -> u16 {
+        match self {
+            Node::Panel(kind) => sizes
+                .get(kind)
+                .copied()
+                .unwrap_or_else(|| kind.default_size()),
+            Node::List(nodes) => nodes
+                .iter()
+                .map(|node| node.size(sizes))
+                .max()
+                .unwrap_or_default(),
+        }
+    }
+
+    fn panels(&self, panels: &mut Vec<PanelKind>) {
+        match self {
+            Node::Panel(kind) => panels.push(*kind),
+            Node::List(nodes) => nodes.iter().for_each(|node| node.panels(panels)),
+        }
+    }
+
+    fn place(
+        &self,
+        area: Rect,
+        vertical: bool,
+        sizes: &BTreeMap<PanelKind, u16>,
+        placed: &mut Vec<(PanelKind, Rect)>,
+    ) {
+        match self {
+            Node::Panel(kind) => placed.push((*kind, area)),
+            Node::List(nodes) => {
+                let parts = nodes
+                    .iter()
+                    .map(|node| {
+                        // NOTE: widths have no minimum, the panels are laid out for any
+                        let min = if vertical {
+                            node.min_height(!vertical)
+                        } else {
+                            0
+                        };
+
+                        (min, node.size(sizes))
+                    })
+                    .collect::<Vec<(u16, u16)>>();
+                let (total, direction) = if vertical {
+                    (area.height, Direction::Vertical)
+                } else {
+                    (area.width, Direction::Horizontal)
+                };
+                let areas = Layout::default()
+                    .direction(direction)
+                    .constraints(split(total, &parts).into_iter().map(Constraint::Length))
+                    .split(area);
+
+                for (node, area) in nodes.iter().zip(areas.iter()) {
+                    node.place(*area, !vertical, sizes, placed);
+                }
+            }
+        }
+    }
+}
+
+/// Share `total` out by the weights of `parts`, parts that would get less
+/// than their minimum get the minimum and the others share the rest
+fn split(total: u16, parts: &[(u16, u16)]) -> Vec<u16> {
+    let mut fixed: Vec<Option<u16>> = vec![None; parts.len()];
+
+    loop {
+        let free = total.saturating_sub(fixed.iter().flatten().sum());
+        let weights: u32 = parts
+            .iter()
+            .zip(&fixed)
+            .filter(|(_, fixed)| fixed.is_none())
+            .map(|((_, weight), _)| *weight as u32)
+            .sum();
+
+        // NOTE: rounding the running total keeps the parts adding up to `free`
+        let mut running = 0;
+        let mut shares = Vec::with_capacity(parts.len());
+        for ((_, weight), fixed) in parts.iter().zip(&fixed) {
+            if fixed.is_none() && weights > 0 {
+                let start = free as u32 * running / weights;
+                running += *weight as u32;
+                shares.push((free as u32 * running / weights - start) as u16);
+            } else {
+                shares.push(0);
+            }
+        }
+
+        let short = parts
+            .iter()
+            .zip(&shares)
+            .zip(&fixed)
+            .position(|(((min, _), share), fixed)| fixed.is_none() && share < min);
+        match short {
+            Some(index) => fixed[index] = Some(parts[index].0),
+            None => {
+                return fixed
+                    .iter()
+                    .zip(shares)
+                    .map(|(fixed, share)| fixed.unwrap_or(share))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Which panels the dashboard shows, where and how big
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DashboardConfig {
+    pub panels: Vec<Node>,
+    /// Share of the room the panel gets next to the others in its list, 1 by
+    /// default and 2 for the PD ports
+    pub sizes: BTreeMap<PanelKind, u16>,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        use PanelKind::*;
+
+        Self {
+            panels: vec![
+                Node::List(vec![
+                    Node::Panel(Charge),
+                    Node::List(vec![
+                        Node::Panel(Brightness),
+                        Node::List(vec![Node::Panel(Privacy), Node::Panel(Smbios)]),
+                    ]),
+                ]),
+                Node::Panel(PdPorts),
+            ],
+            sizes: BTreeMap::new(),
+        }
+    }
+}
+
+impl DashboardConfig {
+    /// Area of every panel that is `shown`
+    pub fn place(&self, area: Rect, shown: impl Fn(PanelKind) -> bool) -> Vec<(PanelKind, Rect)> {
+        let mut placed = Vec::new();
+
+        if let Some(root) = Node::List(self.panels.clone()).prune(&shown) {
+            root.place(area, true, &self.sizes, &mut placed);
+        }
+
+        placed
+    }
+
+    /// Area of every panel that is `shown` in one column, in the configured
+    /// order. The panels get the rows they need, the PD ports also what's left,
+    /// and those that don't fit are left out.
+    pub fn place_compact(
+        &self,
+        area: Rect,
+        shown: impl Fn(PanelKind) -> bool,
+    ) -> Vec<(PanelKind, Rect)> {
+        let mut panels = Vec::new();
+        Node::List(self.panels.clone()).panels(&mut panels);
+
+        let mut left = area.height;
+        let fitting = panels
+            .into_iter()
+            .filter(|kind| shown(*kind))
+            .filter(|kind| {
+                let fits = kind.min_height(true) <= left;
+                if fits {
+                    left -= kind.min_height(true);
+                }
+
+                fits
+            })
+            .collect::<Vec<PanelKind>>();
+
+        let mut y = area.y;
+        fitting
+            .into_iter()
+            .map(|kind| {
+                let mut height = kind.min_height(true);
+                if kind == PanelKind::PdPorts {
+                    height += std::mem::take(&mut left);
+                }
+                let placed = Rect::new(area.x, y, area.width, height);
+                y += height;
+
+                (kind, placed)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+
+    use crate::tui::layout::{split, DashboardConfig, LayoutMode, PanelKind};
+
+    #[test]
+    fn mode_is_picked_by_terminal_size() {
+        let mode = |width, height| LayoutMode::for_area(Rect::new(0, 0, width, height));
+
+        assert_eq!(mode(40, 30), None);
+        assert_eq!(mode(80, 17), None);
+        assert_eq!(mode(80, 24), Some(LayoutMode::Compact));
+        assert_eq!(mode(200, 24), Some(LayoutMode::Compact));
+        assert_eq!(mode(120, 40), Some(LayoutMode::Standard));
+        assert_eq!(mode(200, 50), Some(LayoutMode::Wide));
+    }
+
+    #[test]
+    fn parts_get_their_minimum_and_share_the_rest() {
+        assert_eq!(split(44, &[(15, 1), (8, 2)]), vec![15, 29]);
+        assert_eq!(split(26, &[(15, 1), (8, 2)]), vec![15, 11]);
+        assert_eq!(split(90, &[(0, 1), (0, 1), (0, 1)]), vec![30, 30, 30]);
+        assert_eq!(split(10, &[(15, 1), (8, 2)]), vec![15, 8]);
+    }
+
+    #[test]
+    fn default_dashboard_keeps_the_arrangement_and_hidden_panels_collapse() {
+        let dashboard = DashboardConfig::default();
+        let area = Rect::new(0, 0, 140, 44);
+
+        assert_eq!(
+            dashboard.place(area, |_| true),
+            vec![
+                (PanelKind::Charge, Rect::new(0, 0, 70, 15)),
+                (PanelKind::Brightness, Rect::new(70, 0, 70, 7)),
+                (PanelKind::Privacy, Rect::new(70, 7, 35, 8)),
+                (PanelKind::Smbios, Rect::new(105, 7, 35, 8)),
+                (PanelKind::PdPorts, Rect::new(0, 15, 140, 29)),
+            ]
+        );
+        assert_eq!(
+            dashboard.place(area, |kind| kind != PanelKind::Smbios)[2],
+            (PanelKind::Privacy, Rect::new(70, 7, 70, 8))
+        );
+
+        let compact = dashboard.place_compact(Rect::new(0, 0, 80, 30), |_| true);
+        assert_eq!(
+            compact
+                .iter()
+                .map(|(kind, area)| (*kind, area.height))
+                .collect::<Vec<(PanelKind, u16)>>(),
+            vec![
+                (PanelKind::Charge, 12),
+                (PanelKind::Brightness, 4),
+                (PanelKind::Privacy, 6),
+                (PanelKind::Smbios, 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn panels_are_read_from_nested_lists() {
+        let dashboard: DashboardConfig = toml::from_str(
+            "panels = [[\"brightness\", \"charge\"], \"pd_ports\"]\nsizes = { charge = 3 }",
+        )
+        .unwrap();
+        let placed = dashboard.place(Rect::new(0, 0, 100, 40), |_| true);
+
+        assert_eq!(placed[0], (PanelKind::Brightness, Rect::new(0, 0, 25, 24)));
+        assert_eq!(placed[1], (PanelKind::Charge, Rect::new(25, 0, 75, 24)));
+        assert_eq!(placed[2], (PanelKind::PdPorts, Rect::new(0, 24, 100, 16)));
+
+        let written = toml::to_string(&dashboard).unwrap();
+        assert_eq!(
+            toml::from_str::<DashboardConfig>(&written).unwrap(),
+            dashboard
+        );
+    }
+}
@@ -1,7 +1,15 @@
+pub mod auto;
+pub mod custom;
+pub mod palette;
+
+use std::collections::BTreeSet;
 use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use ratatui::style::Color;
-use serde::{Deserialize, Serialize};
+use crate::tui::theme::palette::ColorSupport;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -107,8 +115,94 @@ impl ThemeVariant {
     }
 }
 
+/// Theme chosen in the config, a built-in palette or one loaded from the
+/// themes directory by its file name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeId {
+    Builtin(ThemeVariant),
+    Custom(&'static str),
+}
+
+impl ThemeId {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemeId::Builtin(variant) => variant.name(),
+            ThemeId::Custom(name) => name,
+        }
+    }
+}
+
+impl From<ThemeVariant> for ThemeId {
+    fn from(variant: ThemeVariant) -> Self {
+        ThemeId::Builtin(variant)
+    }
+}
+
+impl PartialEq<ThemeVariant> for ThemeId {
+    fn eq(&self, other: &ThemeVariant) -> bool {
+        *self == ThemeId::Builtin(*other)
+    }
+}
+
+impl FromStr for ThemeId {
+    type Err = String;
+
+    /// Built-in names as written in the config (`catppuccinmocha`) or on the
+    /// command line (`catppuccin_mocha`), anything else names a custom theme
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err("Theme name can't be empty".to_string());
+        }
+
+        let builtin = ThemeVariant::from_str(s).ok().or_else(|| {
+            ThemeVariant::ALL.into_iter().find(|variant| {
+                toml::Value::try_from(variant)
+                    .is_ok_and(|name| name.as_str() == Some(s.to_lowercase().as_str()))
+            })
+        });
+
+        Ok(match builtin {
+            Some(variant) => ThemeId::Builtin(variant),
+            None => ThemeId::Custom(intern(s)),
+        })
+    }
+}
+
+impl Serialize for ThemeId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ThemeId::Builtin(variant) => variant.serialize(serializer),
+            ThemeId::Custom(name) => serializer.serialize_str(name),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Custom theme names live as long as the app, so that [`ThemeId`] stays `Copy`
+fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+    let mut names = NAMES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(name) = names.get(name) {
+        return name;
+    }
+
+    let name: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    names.insert(name);
+
+    name
+}
+
+#[derive(Debug, Clone)]
 pub struct Theme {
-    pub variant: ThemeVariant,
+    pub id: ThemeId,
     pub text: Color,
     pub background: Color,
     pub border: Color,
@@ -129,6 +223,52 @@ impl Default for Theme {
 }
 
 impl Theme {
+    /// Reduce every colour to what the terminal supports
+    pub fn downsample(self, support: ColorSupport) -> Self {
+        let color = |color| support.downsample(color);
+
+        Self {
+            id: self.id,
+            text: color(self.text),
+            background: color(self.background),
+            border: color(self.border),
+            border_active: color(self.border_active),
+            indication_ok: color(self.indication_ok),
+            indication_warning: color(self.indication_warning),
+            brightness_bar: color(self.brightness_bar),
+            charge_bar: color(self.charge_bar),
+            bar_background: color(self.bar_background),
+            highlighted_text: color(self.highlighted_text),
+            informative_text: color(self.informative_text),
+        }
+    }
+
+    pub fn ok_style(&self) -> Style {
+        let style = Style::default().fg(self.indication_ok);
+
+        if self.indications_distinct() {
+            style
+        } else {
+            style.add_modifier(Modifier::BOLD)
+        }
+    }
+
+    pub fn warning_style(&self) -> Style {
+        let style = Style::default().fg(self.indication_warning);
+
+        if self.indications_distinct() {
+            style
+        } else {
+            style.add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        }
+    }
+
+    /// Whether ok and warning still differ by colour, they can collapse into
+    /// one when the palette is reduced
+    fn indications_distinct(&self) -> bool {
+        self.indication_ok != self.indication_warning && self.indication_ok != Color::Reset
+    }
+
     pub fn from_variant(variant: ThemeVariant) -> Self {
         match variant {
             ThemeVariant::Default => Self::default0(),
@@ -152,7 +292,7 @@ impl Theme {
 
     pub fn default0() -> Self {
         Self {
-            variant: ThemeVariant::Default,
+            id: ThemeId::Builtin(ThemeVariant::Default),
             text: Color::Indexed(7),
             background: Color::Indexed(0),
             border: Color::Indexed(15),
@@ -169,7 +309,7 @@ impl Theme {
 
     pub fn framework() -> Self {
         Self {
-            variant: ThemeVariant::Framework,
+            id: ThemeId::Builtin(ThemeVariant::Framework),
             text: Color::from_str("#F5F5F5").unwrap(),
             background: Color::from_str("#1F1F1F").unwrap(),
             border: Color::from_str("#F45A27").unwrap(),
@@ -186,7 +326,7 @@ impl Theme {
 
     pub fn alucard() -> Self {
         Self {
-            variant: ThemeVariant::Alucard,
+            id: ThemeId::Builtin(ThemeVariant::Alucard),
             text: Color::from_str("#1F1F1F").unwrap(),
             background: Color::from_str("#FFFBEB").unwrap(),
             border: Color::from_str("#A34D14").unwrap(),
@@ -203,7 +343,7 @@ impl Theme {
 
     pub fn catppuccin_frappe() -> Self {
         Self {
-            variant: ThemeVariant::CatppuccinFrappe,
+            id: ThemeId::Builtin(ThemeVariant::CatppuccinFrappe),
             text: Color::from_str("#C6D0F5").unwrap(),
             background: Color::from_str("#232634").unwrap(),
             border: Color::from_str("#EF9F76").unwrap(),
@@ -220,7 +360,7 @@ impl Theme {
 
     pub fn catppuccin_latte() -> Self {
         Self {
-            variant: ThemeVariant::CatppuccinLatte,
+            id: ThemeId::Builtin(ThemeVariant::CatppuccinLatte),
             text: Color::from_str("#4C4F69").unwrap(),
             background: Color::from_str("#DCE0E8").unwrap(),
             border: Color::from_str("#D20F39").unwrap(),
@@ -237,7 +377,7 @@ impl Theme {
 
     pub fn catppuccin_macchiato() -> Self {
         Self {
-            variant: ThemeVariant::CatppuccinMacchiato,
+            id: ThemeId::Builtin(ThemeVariant::CatppuccinMacchiato),
             text: Color::from_str("#CAD3F5").unwrap(),
             background: Color::from_str("#181926").unwrap(),
             border: Color::from_str("#F5A97F").unwrap(),
@@ -254,7 +394,7 @@ impl Theme {
 
     pub fn catppuccin_mocha() -> Self {
         Self {
-            variant: ThemeVariant::CatppuccinMocha,
+            id: ThemeId::Builtin(ThemeVariant::CatppuccinMocha),
             text: Color::from_str("#CDD6F4").unwrap(),
             background: Color::from_str("#11111B").unwrap(),
             border: Color::from_str("#FAB387").unwrap(),
@@ -271,7 +411,7 @@ impl Theme {
 
     pub fn dracula() -> Self {
         Self {
-            variant: ThemeVariant::Dracula,
+            id: ThemeId::Builtin(ThemeVariant::Dracula),
             text: Color::from_str("#F8F8F2").unwrap(),
             background: Color::from_str("#282A36").unwrap(),
             border: Color::from_str("#FFB86C").unwrap(),
@@ -288,7 +428,7 @@ impl Theme {
 
     pub fn gameboy() -> Self {
         Self {
-            variant: ThemeVariant::GameBoy,
+            id: ThemeId::Builtin(ThemeVariant::GameBoy),
             text: Color::from_str("#9A9E3F").unwrap(),
             background: Color::from_str("#1B2A09").unwrap(),
             border: Color::from_str("#496B22").unwrap(),
@@ -305,7 +445,7 @@ impl Theme {
 
     pub fn github_dark() -> Self {
         Self {
-            variant: ThemeVariant::GithubDark,
+            id: ThemeId::Builtin(ThemeVariant::GithubDark),
             text: Color::from_str("#D1D7E0").unwrap(),
             background: Color::from_str("#212830").unwrap(),
             border: Color::from_str("#FF8E40").unwrap(),
@@ -322,7 +462,7 @@ impl Theme {
 
     pub fn github_light() -> Self {
         Self {
-            variant: ThemeVariant::GithubLight,
+            id: ThemeId::Builtin(ThemeVariant::GithubLight),
             text: Color::from_str("#000000").unwrap(),
             background: Color::from_str("#FFFFFF").unwrap(),
             border: Color::from_str("#703100").unwrap(),
@@ -339,7 +479,7 @@ impl Theme {
 
     pub fn gruvbox_dark() -> Self {
         Self {
-            variant: ThemeVariant::GruvboxDark,
+            id: ThemeId::Builtin(ThemeVariant::GruvboxDark),
             text: Color::from_str("#EBDBB2").unwrap(),
             background: Color::from_str("#282828").unwrap(),
             border: Color::from_str("#FE8019").unwrap(),
@@ -356,7 +496,7 @@ impl Theme {
 
     pub fn gruvbox_light() -> Self {
         Self {
-            variant: ThemeVariant::GruvboxLight,
+            id: ThemeId::Builtin(ThemeVariant::GruvboxLight),
             text: Color::from_str("#3C3836").unwrap(),
             background: Color::from_str("#FFFFFF").unwrap(),
             border: Color::from_str("#AF3A03").unwrap(),
@@ -373,7 +513,7 @@ impl Theme {
 
     pub fn monochrome_dark() -> Self {
         Self {
-            variant: ThemeVariant::MonochromeDark,
+            id: ThemeId::Builtin(ThemeVariant::MonochromeDark),
             text: Color::from_str("#FFFFFF").unwrap(),
             background: Color::from_str("#000000").unwrap(),
             border: Color::from_str("#FFFFFF").unwrap(),
@@ -390,7 +530,7 @@ impl Theme {
 
     pub fn monochrome_light() -> Self {
         Self {
-            variant: ThemeVariant::MonochromeLight,
+            id: ThemeId::Builtin(ThemeVariant::MonochromeLight),
             text: Color::from_str("#000000").unwrap(),
             background: Color::from_str("#FFFFFF").unwrap(),
             border: Color::from_str("#000000").unwrap(),
@@ -407,7 +547,7 @@ impl Theme {
 
     pub fn monokai_pro() -> Self {
         Self {
-            variant: ThemeVariant::MonokaiPro,
+            id: ThemeId::Builtin(ThemeVariant::MonokaiPro),
             text: Color::from_str("#FFFFFF").unwrap(),
             background: Color::from_str("#161517").unwrap(),
             border: Color::from_str("#FC9867").unwrap(),
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tui::theme::{ThemeId, ThemeVariant};
+
+/// How long to wait for the terminal to report its background
+pub const QUERY_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Pick `light` or `dark` from the terminal background at startup, `theme`
+/// stays in effect when the terminal doesn't report it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoThemeConfig {
+    pub enabled: bool,
+    pub light: ThemeId,
+    pub dark: ThemeId,
+}
+
+impl Default for AutoThemeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            light: ThemeVariant::GithubLight.into(),
+            dark: ThemeVariant::GithubDark.into(),
+        }
+    }
+}
+
+impl AutoThemeConfig {
+    pub fn pick(&self, background: Background) -> ThemeId {
+        match background {
+            Background::Light => self.light,
+            Background::Dark => self.dark,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    Light,
+    Dark,
+}
+
+/// Parse the answer to an OSC 11 query, e.g. `ESC ]11;rgb:ffff/ffff/ffff ESC \`
+pub fn parse_background(response: &[u8]) -> Option<Background> {
+    let response = String::from_utf8_lossy(response);
+    let start = response.find("]11;")? + 4;
+    let color = response[start..]
+        .split(['\x07', '\x1b'])
+        .next()?
+        .strip_prefix("rgb")?
+        .trim_start_matches('a')
+        .strip_prefix(':')?;
+
+    // NOTE: every component has 1 to 4 hex digits, scale them all to 0..1
+    let mut channels = color.split('/').take(3).map(|channel| {
+        let value = u32::from_str_radix(channel, 16).ok()?;
+        let max = (1u32 << (4 * channel.len().clamp(1, 4))) - 1;
+
+        Some(value as f32 / max as f32)
+    });
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+
+    Some(if luminance > 0.5 {
+        Background::Light
+    } else {
+        Background::Dark
+    })
+}
+
+/// Ask the terminal for its background colour. The query is followed by a
+/// device attributes request that every terminal answers, so terminals
+/// without OSC 11 support don't make us wait for the whole `timeout`.
+#[cfg(unix)]
+pub fn detect_background(timeout: Duration) -> Option<Background> {
+    use std::fs::OpenOptions;
+    use std::io::{IsTerminal, Read, Write};
+    use std::os::fd::AsRawFd;
+    use std::time::Instant;
+
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    crossterm::terminal::enable_raw_mode().ok()?;
+
+    let mut response = Vec::new();
+    let deadline = Instant::now() + timeout;
+    if tty.write_all(b"\x1b]11;?\x1b\\\x1b[c").is_ok() && tty.flush().is_ok() {
+        let mut buf = [0u8; 64];
+
+        while !answered(&response) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let mut fd = libc::pollfd {
+                fd: tty.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: `fd` is a single valid pollfd that outlives the call
+            let ready = unsafe { libc::poll(&mut fd, 1, remaining.as_millis() as libc::c_int) };
+            if ready <= 0 {
+                break;
+            }
+
+            match tty.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => response.extend_from_slice(&buf[..n]),
+            }
+        }
+    }
+    let _ = crossterm::terminal::disable_raw_mode();
+
+    parse_background(&response)
+}
+
+#[cfg(windows)]
+pub fn detect_background(_timeout: Duration) -> Option<Background> {
+    // NOTE: the console input API doesn't hand the query answer back to us
+    None
+}
+
+/// Whether the device attributes answer, `ESC [ ? ... c`, has arrived
+#[cfg(unix)]
+fn answered(response: &[u8]) -> bool {
+    response
+        .windows(3)
+        .position(|window| window == b"\x1b[?")
+        .is_some_and(|start| response[start..].contains(&b'c'))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tui::theme::{
+        auto::{parse_background, AutoThemeConfig, Background},
+        ThemeVariant,
+    };
+
+    #[test]
+    fn background_is_parsed_from_the_osc_11_answer() {
+        assert_eq!(
+            parse_background(b"\x1b]11;rgb:ffff/ffff/fefe\x1b\\\x1b[?62;22c"),
+            Some(Background::Light)
+        );
+        assert_eq!(
+            parse_background(b"\x1b]11;rgb:1e/1e/2e\x07"),
+            Some(Background::Dark)
+        );
+        assert_eq!(
+            parse_background(b"\x1b]11;rgba:fafa/f4f4/eded/ffff\x1b\\"),
+            Some(Background::Light)
+        );
+        assert_eq!(parse_background(b"\x1b[?1;2c"), None);
+    }
+
+    #[test]
+    fn pair_is_picked_by_background() {
+        let auto = AutoThemeConfig::default();
+
+        assert_eq!(auto.pick(Background::Light), ThemeVariant::GithubLight);
+        assert_eq!(auto.pick(Background::Dark), ThemeVariant::GithubDark);
+    }
+}
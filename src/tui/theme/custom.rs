@@ -0,0 +1,230 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ratatui::style::Color;
+use serde::{de::Error, Deserialize, Deserializer};
+
+use crate::{
+    paths,
+    tui::theme::{intern, Theme, ThemeId, ThemeVariant},
+};
+
+/// Directory custom themes are loaded from, one `<name>.toml` per theme
+pub fn themes_dir() -> color_eyre::Result<PathBuf> {
+    Ok(paths::config_dir()?.join("themes"))
+}
+
+/// Colour slots of a theme file. Colours are written as hex (`"#1e1e2e"`),
+/// by name (`"lightblue"`) or as an index into the terminal palette (`208`).
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    #[serde(deserialize_with = "color")]
+    text: Color,
+    #[serde(deserialize_with = "color")]
+    background: Color,
+    #[serde(deserialize_with = "color")]
+    border: Color,
+    #[serde(deserialize_with = "color")]
+    border_active: Color,
+    #[serde(deserialize_with = "color")]
+    indication_ok: Color,
+    #[serde(deserialize_with = "color")]
+    indication_warning: Color,
+    #[serde(deserialize_with = "color")]
+    brightness_bar: Color,
+    #[serde(deserialize_with = "color")]
+    charge_bar: Color,
+    #[serde(deserialize_with = "color")]
+    bar_background: Color,
+    #[serde(deserialize_with = "color")]
+    highlighted_text: Color,
+    #[serde(deserialize_with = "color")]
+    informative_text: Color,
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Index(u8),
+        Name(String),
+    }
+
+    match Value::deserialize(deserializer)? {
+        Value::Index(index) => Ok(Color::Indexed(index)),
+        Value::Name(name) => Color::from_str(&name)
+            .map_err(|_| D::Error::custom(format!("invalid colour \"{}\"", name))),
+    }
+}
+
+/// Parse a theme file, the theme is named after the file
+pub fn parse(name: &str, content: &str) -> Result<Theme, toml::de::Error> {
+    let file: ThemeFile = toml::from_str(content)?;
+
+    Ok(Theme {
+        id: ThemeId::Custom(intern(name)),
+        text: file.text,
+        background: file.background,
+        border: file.border,
+        border_active: file.border_active,
+        indication_ok: file.indication_ok,
+        indication_warning: file.indication_warning,
+        brightness_bar: file.brightness_bar,
+        charge_bar: file.charge_bar,
+        bar_background: file.bar_background,
+        highlighted_text: file.highlighted_text,
+        informative_text: file.informative_text,
+    })
+}
+
+/// Built-in themes followed by the custom ones, in the order `n` cycles through them
+#[derive(Debug, Clone, Default)]
+pub struct Themes {
+    custom: Vec<Theme>,
+}
+
+impl Themes {
+    /// Load the custom themes from [`themes_dir`], files that can't be read
+    /// or parsed are skipped and reported
+    pub fn load() -> (Self, Vec<String>) {
+        match themes_dir() {
+            Ok(dir) => Self::load_from(&dir),
+            Err(e) => (Self::default(), vec![e.to_string()]),
+        }
+    }
+
+    pub fn load_from(dir: &Path) -> (Self, Vec<String>) {
+        let mut themes = Self::default();
+        let mut errors = Vec::new();
+
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return (themes, errors);
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            // NOTE: a custom theme can't shadow a built-in one, it would never be selected
+            if matches!(name.parse(), Ok(ThemeId::Builtin(_))) {
+                errors.push(format!(
+                    "Theme {} has the name of a built-in theme",
+                    path.display()
+                ));
+                continue;
+            }
+
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| parse(name, &content).map_err(|e| e.message().to_string()));
+            match result {
+                Ok(theme) => themes.custom.push(theme),
+                Err(e) => errors.push(format!("Invalid theme {}: {}", path.display(), e)),
+            }
+        }
+
+        (themes, errors)
+    }
+
+    /// Every theme id in cycle order
+    pub fn ids(&self) -> Vec<ThemeId> {
+        ThemeVariant::ALL
+            .into_iter()
+            .map(ThemeId::Builtin)
+            .chain(self.custom.iter().map(|theme| theme.id))
+            .collect()
+    }
+
+    /// Colours of `id`, `None` for a custom theme that isn't loaded
+    pub fn get(&self, id: ThemeId) -> Option<Theme> {
+        match id {
+            ThemeId::Builtin(variant) => Some(Theme::from_variant(variant)),
+            ThemeId::Custom(_) => self.custom.iter().find(|theme| theme.id == id).cloned(),
+        }
+    }
+
+    pub fn next(&self, id: ThemeId) -> ThemeId {
+        let ids = self.ids();
+        let index = ids.iter().position(|other| *other == id);
+
+        index.map_or(ids[0], |index| ids[(index + 1) % ids.len()])
+    }
+
+    pub fn previous(&self, id: ThemeId) -> ThemeId {
+        let ids = self.ids();
+        let index = ids.iter().position(|other| *other == id);
+
+        index.map_or(ids[0], |index| ids[(index + ids.len() - 1) % ids.len()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::tui::theme::{
+        custom::{parse, Themes},
+        ThemeId, ThemeVariant,
+    };
+
+    const NORD: &str = r##"
+        text = "#d8dee9"
+        background = "black"
+        border = 4
+        border_active = "lightcyan"
+        indication_ok = "#a3be8c"
+        indication_warning = "#bf616a"
+        brightness_bar = "#ebcb8b"
+        charge_bar = "#a3be8c"
+        bar_background = "#3b4252"
+        highlighted_text = "#88c0d0"
+        informative_text = "8"
+    "##;
+
+    #[test]
+    fn theme_file_accepts_hex_named_and_indexed_colours() {
+        let theme = parse("nord", NORD).unwrap();
+
+        assert_eq!(theme.id, ThemeId::Custom("nord"));
+        assert_eq!(theme.text, Color::Rgb(0xd8, 0xde, 0xe9));
+        assert_eq!(theme.background, Color::Black);
+        assert_eq!(theme.border, Color::Indexed(4));
+        assert_eq!(theme.border_active, Color::LightCyan);
+        assert_eq!(theme.informative_text, Color::Indexed(8));
+
+        let missing = NORD.replace("charge_bar = \"#a3be8c\"", "");
+        assert!(parse("nord", &missing).is_err());
+        let invalid = NORD.replace("\"black\"", "\"blackish\"");
+        assert!(parse("nord", &invalid)
+            .unwrap_err()
+            .message()
+            .contains("blackish"));
+    }
+
+    #[test]
+    fn custom_themes_follow_the_built_in_ones() {
+        let dir = std::env::temp_dir().join(format!("themes-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("nord.toml"), NORD).unwrap();
+        std::fs::write(dir.join("dracula.toml"), NORD).unwrap();
+        std::fs::write(dir.join("broken.toml"), "text = 1").unwrap();
+
+        let (themes, errors) = Themes::load_from(&dir);
+        let nord = ThemeId::Custom("nord");
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(themes.next(ThemeVariant::MonokaiPro.into()), nord);
+        assert_eq!(themes.next(nord), ThemeVariant::Default);
+        assert_eq!(themes.previous(ThemeVariant::Default.into()), nord);
+        assert!(themes.get(nord).is_some());
+        assert!(themes.get(ThemeId::Custom("missing")).is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+/// Colours the terminal can show, themes are reduced to what it supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorSupport {
+    /// Detect from `NO_COLOR`, `COLORTERM` and `TERM`
+    #[default]
+    Auto,
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    None,
+}
+
+impl ColorSupport {
+    /// Replace [`ColorSupport::Auto`] with what the environment reports
+    pub fn resolve(self) -> Self {
+        match self {
+            ColorSupport::Auto => Self::from_env(|name| std::env::var(name).ok()),
+            support => support,
+        }
+    }
+
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        // NOTE: https://no-color.org, any non-empty value disables colours
+        if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return ColorSupport::None;
+        }
+        if var("COLORTERM").is_some_and(|value| value == "truecolor" || value == "24bit") {
+            return ColorSupport::TrueColor;
+        }
+
+        match var("TERM") {
+            // Windows terminals don't set TERM and all handle RGB
+            None => ColorSupport::TrueColor,
+            Some(term) if term == "dumb" => ColorSupport::None,
+            Some(term) if term.contains("direct") || term.contains("truecolor") => {
+                ColorSupport::TrueColor
+            }
+            Some(term) if term.contains("256") => ColorSupport::Ansi256,
+            Some(_) => ColorSupport::Ansi16,
+        }
+    }
+
+    /// Closest colour the terminal can show
+    pub fn downsample(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorSupport::None, _) => Color::Reset,
+            (ColorSupport::Ansi256, Color::Rgb(r, g, b)) => {
+                Color::Indexed(nearest(16..=255, (r, g, b)))
+            }
+            (ColorSupport::Ansi16, Color::Rgb(r, g, b)) => {
+                ANSI16[nearest(0..=15, (r, g, b)) as usize]
+            }
+            (ColorSupport::Ansi16, Color::Indexed(index)) if index < 16 => ANSI16[index as usize],
+            (ColorSupport::Ansi16, Color::Indexed(index)) => {
+                ANSI16[nearest(0..=15, indexed_rgb(index)) as usize]
+            }
+            (_, color) => color,
+        }
+    }
+}
+
+/// The first 16 palette entries by index
+const ANSI16: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// xterm's defaults, terminals differ but they're close enough to match against
+const ANSI16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// RGB value of a 256-colour palette entry
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16_RGB[index as usize],
+        16..=231 => {
+            let cube = index - 16;
+
+            (
+                CUBE_LEVELS[(cube / 36) as usize],
+                CUBE_LEVELS[(cube / 6 % 6) as usize],
+                CUBE_LEVELS[(cube % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let grey = 8 + 10 * (index - 232);
+
+            (grey, grey, grey)
+        }
+    }
+}
+
+fn nearest(indices: std::ops::RangeInclusive<u8>, (r, g, b): (u8, u8, u8)) -> u8 {
+    let distance = |index: &u8| {
+        let (ir, ig, ib) = indexed_rgb(*index);
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+
+        d(r, ir) + d(g, ig) + d(b, ib)
+    };
+
+    indices.min_by_key(distance).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::tui::theme::palette::ColorSupport;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn support_is_detected_from_the_environment() {
+        let detect = |vars: &[(&str, &str)]| ColorSupport::from_env(env(vars));
+
+        assert_eq!(
+            detect(&[("COLORTERM", "truecolor"), ("TERM", "xterm")]),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), ColorSupport::Ansi256);
+        assert_eq!(detect(&[("TERM", "linux")]), ColorSupport::Ansi16);
+        assert_eq!(detect(&[("TERM", "dumb")]), ColorSupport::None);
+        assert_eq!(
+            detect(&[("NO_COLOR", "1"), ("COLORTERM", "truecolor")]),
+            ColorSupport::None
+        );
+        assert_eq!(detect(&[("NO_COLOR", "")]), ColorSupport::TrueColor);
+    }
+
+    #[test]
+    fn colours_are_mapped_to_the_nearest_palette_entry() {
+        let orange = Color::Rgb(0xf2, 0x64, 0x22);
+
+        assert_eq!(ColorSupport::TrueColor.downsample(orange), orange);
+        assert_eq!(
+            ColorSupport::Ansi256.downsample(orange),
+            Color::Indexed(202)
+        );
+        assert_eq!(ColorSupport::Ansi16.downsample(orange), Color::LightRed);
+        assert_eq!(
+            ColorSupport::Ansi16.downsample(Color::Indexed(244)),
+            Color::DarkGray
+        );
+        assert_eq!(
+            ColorSupport::Ansi16.downsample(Color::Indexed(9)),
+            Color::LightRed
+        );
+        assert_eq!(ColorSupport::None.downsample(orange), Color::Reset);
+    }
+}